@@ -1,6 +1,8 @@
 use std::num::NonZeroU8;
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
 use lonelybot::{
     deck::{Deck, N_PILE_CARDS},
     engine::SolitaireEngine,
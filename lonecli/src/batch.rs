@@ -0,0 +1,273 @@
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Write},
+    num::NonZeroU8,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::channel,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use lonelybot::{
+    convert::convert_moves,
+    shuffler::{encode_shuffle, CardDeck},
+    solver::{solve_with_tracking, SearchResult},
+    standard::StandardSolitaire,
+    state::Solitaire,
+    tracking::TerminateSignal,
+};
+use serde_json::{json, Deserializer, Value};
+
+use crate::{
+    solver::STACK_SIZE, solvitaire::parse_solvitaire, tracking::AtomicSearchStats, try_shuffle,
+    Seed, SeedType,
+};
+
+// checking the clock on every visit is wasteful
+const CHECK_INTERVAL: u32 = 1024;
+
+pub(crate) struct Deal {
+    /// How the deal is written in the input
+    pub(crate) name: String,
+    pub(crate) deck: CardDeck,
+    /// Overrides the draw step of the command
+    pub(crate) draw_step: Option<NonZeroU8>,
+    /// When the deal was played, if known
+    pub(crate) timestamp: Option<String>,
+}
+
+/// Parse the batch input
+///
+/// Each deal is either a line `<seed type> <seed>` or a Solvitaire JSON document,
+/// blank lines and lines starting with `#` are skipped
+fn parse_deals(input: &str) -> Result<Vec<Deal>, String> {
+    let mut deals = Vec::new();
+    let mut rest = input;
+    let mut line_no = 0;
+
+    while !rest.is_empty() {
+        let trimmed = rest.trim_start();
+        line_no += rest[..rest.len() - trimmed.len()].matches('\n').count();
+        rest = trimmed;
+
+        if rest.starts_with('{') {
+            let mut stream = Deserializer::from_str(rest).into_iter::<Value>();
+            let value = match stream.next() {
+                Some(Ok(value)) => value,
+                Some(Err(err)) => return Err(format!("line {}: {err}", line_no + 1)),
+                None => break,
+            };
+            let deck = parse_solvitaire(&value)
+                .ok_or_else(|| format!("line {}: invalid Solvitaire deal", line_no + 1))?;
+            let len = stream.byte_offset();
+            line_no += rest[..len].matches('\n').count();
+            deals.push(Deal {
+                name: format!("json:{}", line_no + 1),
+                deck,
+                draw_step: None,
+                timestamp: None,
+            });
+            rest = &rest[len..];
+            continue;
+        }
+
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+        let line = line.trim();
+        rest = next;
+        line_no += 1;
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let deal = line
+            .split_once(char::is_whitespace)
+            .and_then(|(seed_type, seed)| {
+                let seed_type = SeedType::from_str(seed_type, true).ok()?;
+                let (seed, draw_step) = (seed.trim(), Seed::code_draw_step(seed_type, seed.trim()));
+                let seed = Seed::parse(seed_type, seed)?;
+                Some(Deal {
+                    deck: try_shuffle(&seed)?,
+                    name: seed.to_string(),
+                    // a deal code has its own draw step
+                    draw_step,
+                    timestamp: None,
+                })
+            })
+            .ok_or_else(|| format!("line {line_no}: invalid deal `{line}`"))?;
+        deals.push(deal);
+    }
+    Ok(deals)
+}
+
+struct Deadline<'a> {
+    end: Instant,
+    terminated: &'a AtomicBool,
+    counter: Cell<u32>,
+    timeout: Cell<bool>,
+}
+
+impl TerminateSignal for Deadline<'_> {
+    fn is_terminated(&self) -> bool {
+        if self.timeout.get() || self.terminated.load(Ordering::Relaxed) {
+            return true;
+        }
+        let cnt = self.counter.get().wrapping_add(1);
+        self.counter.set(cnt);
+        if cnt.is_multiple_of(CHECK_INTERVAL) && Instant::now() >= self.end {
+            self.timeout.set(true);
+        }
+        self.timeout.get()
+    }
+}
+
+fn solve_deal(
+    index: usize,
+    deal: &Deal,
+    draw_step: NonZeroU8,
+    timeout: Duration,
+    terminated: &AtomicBool,
+) -> Value {
+    let draw_step = deal.draw_step.unwrap_or(draw_step);
+    let game = Solitaire::new(&deal.deck, draw_step);
+    let stats = AtomicSearchStats::new();
+    let now = Instant::now();
+    let sign = Deadline {
+        end: now + timeout,
+        terminated,
+        counter: Cell::new(0),
+        timeout: Cell::new(false),
+    };
+
+    let (result, history) = solve_with_tracking(&mut game.clone(), &stats, &sign);
+    let elapsed = now.elapsed();
+
+    let moves = history.and_then(|h| convert_moves(&mut StandardSolitaire::from(&game), &h).ok());
+
+    let mut record = json!({
+        "index": index,
+        "deal": deal.name,
+        "exact": encode_shuffle(deal.deck).map(|x| x.to_string()),
+        "draw_step": draw_step.get(),
+        "result": if sign.timeout.get() && result == SearchResult::Terminated {
+            json!("Timeout")
+        } else {
+            json!(result)
+        },
+        "moves": moves.as_ref().map(|m| m.as_slice()),
+        "total_visit": stats.total_visit(),
+        "unique_visit": stats.unique_visit(),
+        "time_ms": elapsed.as_millis(),
+    });
+    if let Some(timestamp) = &deal.timestamp {
+        record["timestamp"] = json!(timestamp);
+    }
+    record
+}
+
+/// Solve all the deals in the file at `path` and write one JSON record per deal (in the input order)
+pub(crate) fn batch(
+    path: &str,
+    draw_step: NonZeroU8,
+    timeout: Duration,
+    threads: Option<usize>,
+    out: Option<&str>,
+    terminated: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let input = fs::read_to_string(path)?;
+    let deals =
+        parse_deals(&input).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    solve_deals(&deals, draw_step, timeout, threads, out, terminated)
+}
+
+/// Solve the `deals` in parallel and write one JSON record per deal (in the given order)
+pub(crate) fn solve_deals(
+    deals: &[Deal],
+    draw_step: NonZeroU8,
+    timeout: Duration,
+    threads: Option<usize>,
+    out: Option<&str>,
+    terminated: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let mut out: Box<dyn Write> = match out {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let n_threads =
+        threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+
+    let next = AtomicUsize::new(0);
+    let (send, recv) = channel::<(usize, Value)>();
+
+    thread::scope(|s| {
+        for _ in 0..n_threads.max(1) {
+            let send = send.clone();
+            let next = &next;
+            thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(s, move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= deals.len() || terminated.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let record = solve_deal(i, &deals[i], draw_step, timeout, terminated);
+                    send.send((i, record)).ok();
+                })
+                .unwrap();
+        }
+        drop(send);
+
+        // the records can finish out of order
+        let mut pending = BTreeMap::new();
+        let mut n_written = 0;
+        for (i, record) in recv {
+            pending.insert(i, record);
+            while let Some(record) = pending.remove(&n_written) {
+                writeln!(out, "{record}")?;
+                out.flush()?;
+                n_written += 1;
+            }
+        }
+        eprintln!("Processed {n_written}/{} deals", deals.len());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deals() {
+        let input = r#"
+# comment
+default 12
+exact 0
+{"tableau piles": [["3D"],["Ad","8H"],["Qs","9d","8C"],["Jh","2c","3c","AC"],
+  ["9c","As","10s","Qc","4S"],["6d","6h","Qh","4d","6s","8D"],["2h","Ks","Js","2s","5d","6c","4H"]],
+ "stock": ["QD","10H","3S","5S","8S","7D","KC","JD","9H","JC","4C","5H","10D","AH","7C","9S","3H","7H","5C","7S","10C","2D","KD","KH"],
+ "waste": [], "foundation": [[],[],[],[]]}
+klondike-solver 0
+code K3-4PePhQESEJFQKYxbqbrBGzPLpQ3k3ZqqmzeLFeGNh
+"#;
+        let deals = parse_deals(input).unwrap();
+        assert_eq!(deals.len(), 5);
+        assert_eq!(deals[0].name, "L-12");
+        assert_eq!(deals[0].deck, lonelybot::shuffler::default_shuffle(12));
+        assert_eq!(deals[2].deck, lonelybot::shuffler::ks_shuffle(0));
+        assert_eq!(deals[2].deck, deals[3].deck);
+        assert_eq!(deals[4].deck, deals[0].deck);
+        assert_eq!(deals[4].draw_step, NonZeroU8::new(3));
+        assert_eq!(deals[0].draw_step, None);
+
+        assert!(parse_deals("default").is_err());
+        assert!(parse_deals("solvitaire 99999999999").is_err());
+        assert!(parse_deals(r#"{"tableau piles": []}"#).is_err());
+    }
+}
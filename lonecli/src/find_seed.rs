@@ -0,0 +1,229 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use clap::Args;
+use lonelybot::{
+    card::{Card, N_CARDS},
+    deck::{N_PILES, N_PILE_CARDS},
+    shuffler::{self, CardDeck},
+};
+use serde_json::json;
+
+use crate::{OutputFormat, Seed, SeedType};
+
+// the seeds are handed to the threads in blocks
+const BLOCK_SIZE: u64 = 1 << 16;
+const PROGRESS_STEP: u64 = 1 << 26;
+
+#[derive(Args, Clone)]
+pub(crate) struct FindSeedOptions {
+    /// Face up cards of the tableau from the left pile, `?` for an unknown card (e.g. "QH 10S ? 5C")
+    #[arg(long, default_value = "")]
+    top: String,
+    /// Cards of the stock from its top, `?` for an unknown card
+    #[arg(long, default_value = "")]
+    stock: String,
+    /// First seed to try
+    #[arg(long, default_value_t = 0)]
+    start: u64,
+    /// Stop before this seed (default to 2^32)
+    #[arg(long, default_value_t = 1 << 32)]
+    end: u64,
+    /// Number of threads (default to the number of cores)
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+type Dealer = fn(u64) -> CardDeck;
+
+/// The known cards of a deal, by their position in the `CardDeck`
+struct Pattern(Vec<(usize, Card)>);
+
+impl Pattern {
+    fn parse(top: &str, stock: &str) -> Result<Self, String> {
+        let tokens = |s: &str| -> Vec<String> {
+            s.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|x| !x.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let parse_card = |x: &str| -> Result<Option<Card>, String> {
+            if x == "?" {
+                Ok(None)
+            } else {
+                x.parse()
+                    .map(Some)
+                    .map_err(|_| format!("invalid card `{x}`"))
+            }
+        };
+
+        let top = tokens(top);
+        let stock = tokens(stock);
+        if top.len() > usize::from(N_PILES) {
+            return Err(format!("at most {N_PILES} top cards"));
+        }
+
+        let mut known = Vec::new();
+        for (i, x) in top.iter().enumerate() {
+            if let Some(c) = parse_card(x)? {
+                known.push((i * (i + 1) / 2 + i, c));
+            }
+        }
+        for (i, x) in stock.iter().enumerate() {
+            if let Some(c) = parse_card(x)? {
+                known.push((usize::from(N_PILE_CARDS) + i, c));
+            }
+        }
+        if known.iter().any(|&(i, _)| i >= usize::from(N_CARDS)) {
+            return Err("too many stock cards".to_string());
+        }
+        if known.is_empty() {
+            return Err("no known card".to_string());
+        }
+        Ok(Self(known))
+    }
+
+    fn matches(&self, cards: &CardDeck) -> bool {
+        self.0.iter().all(|&(i, c)| cards[i] == c)
+    }
+}
+
+/// The shuffler of the seed types which can be enumerated
+#[allow(clippy::cast_possible_truncation)]
+fn dealer(seed_type: SeedType) -> Option<(Dealer, u64)> {
+    const U32_END: u64 = 1 << 32;
+    match seed_type {
+        // far enough from the overflow of the block counter
+        SeedType::Default => Some((shuffler::default_shuffle, u64::MAX >> 1)),
+        SeedType::Solvitaire => Some((|s| shuffler::solvitaire_shuffle(s as u32), U32_END)),
+        SeedType::KlondikeSolver => Some((|s| shuffler::ks_shuffle(s as u32), U32_END)),
+        SeedType::Greenfelt => Some((|s| shuffler::greenfelt_shuffle(s as u32), U32_END)),
+        SeedType::Pysol => Some((|s| shuffler::pysol_shuffle(s.into()), u64::MAX >> 1)),
+        SeedType::Exact | SeedType::Microsoft | SeedType::Code => None,
+    }
+}
+
+/// Try every seed of `start..end` in parallel and call `found` for each match (in no particular order)
+fn search(
+    deal: Dealer,
+    pattern: &Pattern,
+    (start, end): (u64, u64),
+    n_threads: usize,
+    terminated: &AtomicBool,
+    found: &(dyn Fn(u64) + Sync),
+) -> u64 {
+    let next = AtomicU64::new(start);
+    let n_tried = AtomicU64::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..n_threads.max(1) {
+            s.spawn(|| loop {
+                let from = next.fetch_add(BLOCK_SIZE, Ordering::Relaxed);
+                if from >= end || terminated.load(Ordering::Relaxed) {
+                    break;
+                }
+                let to = from.saturating_add(BLOCK_SIZE).min(end);
+                for seed in from..to {
+                    if pattern.matches(&deal(seed)) {
+                        found(seed);
+                    }
+                }
+                let prev = n_tried.fetch_add(to - from, Ordering::Relaxed);
+                if (prev + to - from) / PROGRESS_STEP > prev / PROGRESS_STEP {
+                    eprintln!("Tried {} seeds", prev + to - from);
+                }
+            });
+        }
+    });
+    n_tried.into_inner()
+}
+
+/// Report every seed of `seed_type` whose deal has the known cards
+pub(crate) fn find_seed(
+    seed_type: SeedType,
+    options: &FindSeedOptions,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let invalid = |err: String| io::Error::new(io::ErrorKind::InvalidInput, err);
+
+    let (deal, max_end) =
+        dealer(seed_type).ok_or_else(|| invalid("this seed type can't be enumerated".into()))?;
+    let pattern = Pattern::parse(&options.top, &options.stock).map_err(invalid)?;
+    let range = (options.start, options.end.min(max_end));
+    let n_threads = options
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+
+    let now = Instant::now();
+    let n_found = AtomicUsize::new(0);
+    let found = |seed: u64| {
+        let seed = Seed {
+            seed_type,
+            seed: seed.into(),
+        };
+        let mut out = io::stdout().lock();
+        if format == OutputFormat::Json {
+            writeln!(out, "{}", json!({"seed": seed.to_string()})).ok();
+        } else {
+            writeln!(out, "{seed}").ok();
+        }
+        n_found.fetch_add(1, Ordering::Relaxed);
+    };
+
+    let n_tried = search(deal, &pattern, range, n_threads, terminated, &found);
+    eprintln!(
+        "Found {} seeds in {n_tried} tried in {:.2} s",
+        n_found.into_inner(),
+        now.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let cards = shuffler::greenfelt_shuffle(1234);
+        let top: Vec<String> = (0..7)
+            .map(|i| {
+                if i == 3 {
+                    "?".to_string()
+                } else {
+                    cards[i * (i + 1) / 2 + i].to_string()
+                }
+            })
+            .collect();
+        let stock = format!("? {}", cards[29]);
+        let pattern = Pattern::parse(&top.join(" "), &stock).unwrap();
+        assert_eq!(pattern.0.len(), 7);
+
+        let (deal, _) = dealer(SeedType::Greenfelt).unwrap();
+        let results = Mutex::new(Vec::new());
+        let n_tried = search(
+            deal,
+            &pattern,
+            (1000, 20_000),
+            4,
+            &AtomicBool::new(false),
+            &|seed| results.lock().unwrap().push(seed),
+        );
+        assert_eq!(n_tried, 19_000);
+        assert_eq!(results.into_inner().unwrap(), vec![1234]);
+
+        assert!(Pattern::parse("", "").is_err());
+        assert!(Pattern::parse("? ? ? ? ? ? ? ?", "").is_err());
+        assert!(Pattern::parse("XX", "").is_err());
+    }
+}
@@ -1,22 +1,36 @@
+mod batch;
+mod find_seed;
+mod mine;
+mod mscl;
+mod render;
+mod report;
 mod solver;
 mod solvitaire;
+mod split;
 mod tracking;
 mod tui;
+mod verify;
 
 use bpci::{Interval, NSuccessesSample, WilsonScore};
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use lonelybot::convert::convert_moves;
+use lonelybot::deal_code::DealCode;
 // use lonelybot::dependencies::DependencyEngine;
+use lonelybot::difficulty::{rate_difficulty, DifficultyConfig};
 use lonelybot::engine::SolitaireEngine;
+use lonelybot::estimate::estimate_tree;
+use lonelybot::incremental::IncrementalSolver;
 use lonelybot::mcts_solver::pick_moves;
+use lonelybot::moves::Move;
+use lonelybot::notation::{to_klondike_solver, to_minimal_klondike};
 use lonelybot::pruning::{CyclePruner, FullPruner, NoPruner};
 use lonelybot::shuffler::{self, CardDeck, U256};
-use lonelybot::state::{Encode, Solitaire};
-use lonelybot::tracking::DefaultTerminateSignal;
+use lonelybot::state::Solitaire;
+use lonelybot::tracking::{DefaultTerminateSignal, TerminateSignal};
 use lonelybot::traverse::Control;
 use rand::prelude::*;
+use serde_json::{json, Value};
 use solvitaire::Solvitaire;
-use std::collections::HashSet;
 use std::fs::File;
 use std::num::NonZeroU8;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -26,9 +40,7 @@ use std::{io::Write, time::Instant};
 use std::{thread, time};
 
 use lonelybot::solver::SearchResult;
-use lonelybot::standard::{Pos, StandardHistoryVec, StandardSolitaire};
-
-use crate::tui::print_game;
+use lonelybot::standard::StandardSolitaire;
 
 #[derive(ValueEnum, Clone, Copy)]
 enum SeedType {
@@ -39,6 +51,18 @@ enum SeedType {
     Greenfelt,
     Exact,
     Microsoft,
+    /// The numbered Klondike deals of PySolFC
+    Pysol,
+    /// A deal code printed by `exact`, e.g. `K3-...`
+    Code,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    /// A JSON object (one per line for the commands with several results)
+    #[value(alias = "jsonl")]
+    Json,
 }
 
 #[derive(Args, Clone)]
@@ -54,10 +78,7 @@ struct Seed {
 
 impl From<&StringSeed> for Seed {
     fn from(value: &StringSeed) -> Self {
-        Seed {
-            seed_type: value.seed_type,
-            seed: U256::from_dec_str(&value.seed).unwrap(),
-        }
+        Seed::parse(value.seed_type, &value.seed).unwrap()
     }
 }
 
@@ -71,8 +92,9 @@ impl std::fmt::Display for Seed {
                 SeedType::Solvitaire => "S",
                 SeedType::KlondikeSolver => "K",
                 SeedType::Greenfelt => "G",
-                SeedType::Exact => "E",
+                SeedType::Exact | SeedType::Code => "E",
                 SeedType::Microsoft => "M",
+                SeedType::Pysol => "P",
             },
             self.seed
         )
@@ -80,6 +102,30 @@ impl std::fmt::Display for Seed {
 }
 
 impl Seed {
+    /// The deal codes are read as their exact permutation number
+    #[must_use]
+    pub(crate) fn parse(seed_type: SeedType, seed: &str) -> Option<Self> {
+        match seed_type {
+            SeedType::Code => Some(Self {
+                seed_type: SeedType::Exact,
+                seed: seed.parse::<DealCode>().ok()?.exact,
+            }),
+            _ => Some(Self {
+                seed_type,
+                seed: U256::from_dec_str(seed).ok()?,
+            }),
+        }
+    }
+
+    /// The draw step written in a deal code, `None` for the other seed types
+    #[must_use]
+    pub(crate) fn code_draw_step(seed_type: SeedType, seed: &str) -> Option<NonZeroU8> {
+        match seed_type {
+            SeedType::Code => seed.parse::<DealCode>().ok().map(|c| c.draw_step),
+            _ => None,
+        }
+    }
+
     #[must_use]
     pub(crate) const fn seed(&self) -> U256 {
         self.seed
@@ -94,20 +140,29 @@ impl Seed {
     }
 }
 
+/// Return `None` when the seed is out of range for its type
 #[must_use]
-fn shuffle(s: &Seed) -> CardDeck {
+fn try_shuffle(s: &Seed) -> Option<CardDeck> {
     let seed = s.seed;
+    let bits = seed.bits();
     match s.seed_type {
-        SeedType::Default => shuffler::default_shuffle(seed.as_u64()),
-        SeedType::Solvitaire => shuffler::solvitaire_shuffle(seed.as_u32()),
-        SeedType::KlondikeSolver => shuffler::ks_shuffle(seed.as_u32()),
-        SeedType::Greenfelt => shuffler::greenfelt_shuffle(seed.as_u32()),
-        SeedType::Exact => shuffler::exact_shuffle(seed).unwrap(),
-        SeedType::Microsoft => shuffler::microsoft_shuffle(seed).unwrap(),
+        SeedType::Default if bits <= 64 => Some(shuffler::default_shuffle(seed.as_u64())),
+        SeedType::Solvitaire if bits <= 32 => Some(shuffler::solvitaire_shuffle(seed.as_u32())),
+        SeedType::KlondikeSolver if bits <= 32 => Some(shuffler::ks_shuffle(seed.as_u32())),
+        SeedType::Greenfelt if bits <= 32 => Some(shuffler::greenfelt_shuffle(seed.as_u32())),
+        SeedType::Exact | SeedType::Code => shuffler::exact_shuffle(seed),
+        SeedType::Microsoft => shuffler::microsoft_shuffle(seed),
+        SeedType::Pysol if bits <= 128 => Some(shuffler::pysol_shuffle(seed.as_u128())),
+        _ => None,
     }
 }
 
-fn benchmark(seed: &Seed, draw_step: NonZeroU8) {
+#[must_use]
+fn shuffle(s: &Seed) -> CardDeck {
+    try_shuffle(s).unwrap()
+}
+
+fn benchmark(seed: &Seed, draw_step: NonZeroU8, format: OutputFormat) {
     let mut rng = SmallRng::seed_from_u64(seed.seed().as_u64());
 
     let mut total_moves = 0u32;
@@ -126,14 +181,23 @@ fn benchmark(seed: &Seed, draw_step: NonZeroU8) {
             total_moves += 1;
         }
     }
-    println!(
-        "{} {} op/s",
-        total_moves,
-        f64::from(total_moves) / now.elapsed().as_secs_f64()
-    );
+    let ops = f64::from(total_moves) / now.elapsed().as_secs_f64();
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "moves": total_moves,
+                "ops_per_sec": ops,
+            })
+        );
+    } else {
+        println!("{total_moves} {ops} op/s");
+    }
 }
 
-fn do_random(seed: &Seed, draw_step: NonZeroU8) {
+fn do_random(seed: &Seed, draw_step: NonZeroU8, format: OutputFormat) {
     const TOTAL_GAME: u32 = 10000;
 
     let mut total_win = 0;
@@ -157,7 +221,120 @@ fn do_random(seed: &Seed, draw_step: NonZeroU8) {
             game.do_move(*m);
         }
     }
-    println!("Total win {total_win}/{TOTAL_GAME}");
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "solved": total_win,
+                "total": TOTAL_GAME,
+            })
+        );
+    } else {
+        println!("Total win {total_win}/{TOTAL_GAME}");
+    }
+}
+
+fn do_estimate(seed: &Seed, draw_step: NonZeroU8, n_probes: usize, format: OutputFormat) {
+    const BUCKET: usize = 10;
+
+    let mut rng = SmallRng::seed_from_u64(seed.seed().low_u64());
+    let game = Solitaire::new(&shuffle(seed), draw_step);
+
+    let now = Instant::now();
+    let est = estimate_tree(&game, n_probes, &mut rng);
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "time_ms": now.elapsed().as_secs_f64() * 1000f64,
+                "n_probes": est.n_probes,
+                "nodes": est.nodes(),
+                "mean_depth": est.mean_depth(),
+                "max_depth": est.max_depth(),
+                "depth_nodes": est.depth_nodes,
+            })
+        );
+        return;
+    }
+    println!("Run in {} ms", now.elapsed().as_secs_f64() * 1000f64);
+    println!("Estimated nodes: {:.3e}", est.nodes());
+    println!("Mean leaf depth: {:.2}", est.mean_depth());
+    println!("Max depth: {}", est.max_depth());
+    println!("Estimated nodes by depth");
+    for (i, chunk) in est.depth_nodes.chunks(BUCKET).enumerate() {
+        println!(
+            "{:>4}..{:<4} {:.3e}",
+            i * BUCKET,
+            i * BUCKET + chunk.len(),
+            chunk.iter().sum::<f64>()
+        );
+    }
+}
+
+fn do_difficulty(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    config: &DifficultyConfig,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) {
+    struct Signal<'a>(&'a AtomicBool);
+    impl TerminateSignal for Signal<'_> {
+        fn is_terminated(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    let mut rng = SmallRng::seed_from_u64(seed.seed().low_u64());
+    let game = Solitaire::new(&shuffle(seed), draw_step);
+
+    let now = Instant::now();
+    let report = rate_difficulty(&game, config, &mut rng, &Signal(terminated));
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "time_ms": now.elapsed().as_secs_f64() * 1000f64,
+                "result": report.result,
+                "unique_states": report.unique_states,
+                "found_solution_len": report.found_solution_len,
+                "redeals": report.redeals,
+                "root_moves": report.root_moves,
+                "solvable_root_moves": report.solvable_root_moves,
+                "unknown_root_moves": report.unknown_root_moves,
+                "hop_win_rate": report.hop_win_rate,
+                "score": report.score,
+                "tier": report.tier.to_string(),
+            })
+        );
+        return;
+    }
+    println!("Run in {} ms", now.elapsed().as_secs_f64() * 1000f64);
+    println!("Result: {:?}", report.result);
+    println!("Unique states: {}", report.unique_states);
+    if let Some(len) = report.found_solution_len {
+        println!("Length of the solution found: {len}");
+    }
+    if let Some(redeals) = report.redeals {
+        println!("Redeals: {redeals}");
+    }
+    if report.root_moves > 0 {
+        println!(
+            "Solvable first moves: {}/{} ({} unknown)",
+            report.solvable_root_moves, report.root_moves, report.unknown_root_moves
+        );
+    }
+    if let Some(rate) = report.hop_win_rate {
+        println!("HOP win rate: {rate:.4}");
+    }
+    println!("Score: {:.1}", report.score);
+    println!("Difficulty: {}", report.tier);
 }
 
 fn ucb1(n_sucess: usize, n_visit: usize, n_total: usize) -> f64 {
@@ -171,12 +348,14 @@ fn ucb1(n_sucess: usize, n_visit: usize, n_total: usize) -> f64 {
     }
 }
 
-fn do_hop(seed: &Seed, draw_step: NonZeroU8, verbose: bool) -> bool {
+/// Play the game with HOP, return whether it's won and the moves played
+fn do_hop(seed: &Seed, draw_step: NonZeroU8, verbose: bool) -> (bool, Vec<Move>) {
     const N_TIMES: usize = 3000;
     const LIMIT: usize = 1000;
 
     let mut game: SolitaireEngine<NoPruner> = Solitaire::new(&shuffle(seed), draw_step).into();
     let mut rng = SmallRng::seed_from_u64(seed.seed().as_u64());
+    let mut history = Vec::new();
 
     while !game.state().is_win() {
         let mut gg = game.state().clone();
@@ -193,7 +372,7 @@ fn do_hop(seed: &Seed, draw_step: NonZeroU8, verbose: bool) -> bool {
             if verbose {
                 println!("Lost");
             }
-            return false;
+            return (false, history);
         };
         if verbose {
             for m in &best {
@@ -203,41 +382,109 @@ fn do_hop(seed: &Seed, draw_step: NonZeroU8, verbose: bool) -> bool {
         }
         for m in best {
             game.do_move(m);
+            history.push(m);
         }
     }
     if verbose {
         println!("Solved");
     }
-    true
+    (true, history)
 }
 
-fn map_pos(p: Pos) -> char {
-    match p {
-        Pos::Deck => 'A',
-        Pos::Stack(id) => char::from_u32('B' as u32 + u32::from(id)).unwrap(),
-        Pos::Pile(id) => char::from_u32('F' as u32 + u32::from(id)).unwrap(),
-    }
-}
-
-fn print_moves_minimal_klondike(moves: &StandardHistoryVec) {
-    for m in moves {
-        match (m.from, m.to) {
-            (Pos::Deck, Pos::Deck) => print!("@"),
-            (from, to) => print!("{}{} ", map_pos(from), map_pos(to)),
-        }
-    }
-}
-
-fn test_solve(seed: &Seed, draw_step: NonZeroU8, terminated: &Arc<AtomicBool>) {
+fn test_solve(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    checkpoint: Option<&str>,
+    resume: Option<&str>,
+    report: Option<&str>,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) {
+    let verbose = format == OutputFormat::Text;
     let shuffled_deck = shuffle(seed);
 
     let g: Solitaire = Solitaire::new(&shuffled_deck, draw_step);
     let mut g_standard = StandardSolitaire::from(&g);
 
+    // a resumed search keeps saving to the file it was resumed from
+    let checkpoint = checkpoint.or(resume);
+    // the statistics of the search before the checkpoint aren't saved
+    let since = if resume.is_some() {
+        " (since resume)"
+    } else {
+        ""
+    };
+
     let now = Instant::now();
-    let res = solver::run_solve(g, true, terminated);
-    println!("Run in {} ms", now.elapsed().as_secs_f64() * 1000f64);
-    println!("Statistic\n{}", res.1);
+    let res = if checkpoint.is_none() {
+        solver::run_solve(g, verbose, terminated)
+    } else {
+        let inc_solver = match resume {
+            None => IncrementalSolver::new(g),
+            Some(path) => {
+                match std::fs::read(path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|data| IncrementalSolver::load(g, &data).map_err(|e| e.to_string()))
+                {
+                    Ok(inc_solver) => inc_solver,
+                    Err(err) => {
+                        eprintln!("Can't resume from {path}: {err}");
+                        return;
+                    }
+                }
+            }
+        };
+        solver::run_solve_checkpoint(
+            inc_solver,
+            checkpoint.map(str::to_string),
+            verbose,
+            terminated,
+        )
+    };
+
+    if let Some(path) = report {
+        if let Err(err) = report::write_report(
+            path,
+            seed,
+            &shuffled_deck,
+            draw_step,
+            res.0,
+            &res.1,
+            resume.is_some(),
+            now.elapsed(),
+            res.2.as_deref(),
+        ) {
+            eprintln!("Can't write the report to {path}: {err}");
+        }
+    }
+
+    if format == OutputFormat::Json {
+        let standard = res
+            .2
+            .as_ref()
+            .and_then(|m| convert_moves(&mut g_standard, m).ok());
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "result": res.0,
+                "time_ms": now.elapsed().as_secs_f64() * 1000f64,
+                "stats": res.1.to_json(),
+                "resumed": resume.is_some(),
+                "moves": res.2.as_ref().map(|m| m.as_slice()),
+                "standard_moves": standard.as_ref().map(|m| m.as_slice()),
+                "minimal_klondike": standard.as_ref().map(|m| to_minimal_klondike(m)),
+                "klondike_solver": standard
+                    .as_ref()
+                    .and_then(|m| to_klondike_solver(&Solitaire::new(&shuffled_deck, draw_step), m).ok()),
+            })
+        );
+        return;
+    }
+
+    println!("Run in {} ms{since}", now.elapsed().as_secs_f64() * 1000f64);
+    println!("Statistic{since}\n{}", res.1);
     match res.0 {
         SearchResult::Solved => {
             let m = res.2.unwrap();
@@ -267,8 +514,12 @@ fn test_solve(seed: &Seed, draw_step: NonZeroU8, terminated: &Arc<AtomicBool>) {
             }
             println!();
             println!();
-            print_moves_minimal_klondike(&moves);
+            println!("{}", to_minimal_klondike(&moves));
             println!();
+            println!(
+                "{}",
+                to_klondike_solver(&Solitaire::new(&shuffled_deck, draw_step), &moves).unwrap()
+            );
         }
         SearchResult::Unsolvable => println!("Impossible"),
         SearchResult::Terminated => println!("Terminated"),
@@ -276,7 +527,13 @@ fn test_solve(seed: &Seed, draw_step: NonZeroU8, terminated: &Arc<AtomicBool>) {
     }
 }
 
-fn rand_solve(seed: &Seed, draw_step: NonZeroU8, start_seed: u64, terminated: &Arc<AtomicBool>) {
+fn rand_solve(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    start_seed: u64,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) {
     let shuffled_deck = shuffle(seed);
 
     let g: Solitaire = Solitaire::new(&shuffled_deck, draw_step);
@@ -297,10 +554,31 @@ fn rand_solve(seed: &Seed, draw_step: NonZeroU8, start_seed: u64, terminated: &A
         game.do_move(*m);
     }
 
-    println!("{}", Solvitaire(game.state().into()));
+    let deal = Solvitaire(game.state().into()).to_string();
+    let verbose = format == OutputFormat::Text;
+    if verbose {
+        println!("{deal}");
+    }
 
     let now = Instant::now();
-    let res = solver::run_solve(game.into_state(), true, terminated);
+    let res = solver::run_solve(game.into_state(), verbose, terminated);
+    if format == OutputFormat::Json {
+        let deal: Value = serde_json::from_str(&deal).unwrap();
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "start_seed": start_seed,
+                "deal": deal,
+                "result": res.0,
+                "time_ms": now.elapsed().as_secs_f64() * 1000f64,
+                "stats": res.1.to_json(),
+                "moves": res.2.as_ref().map(|m| m.as_slice()),
+            })
+        );
+        return;
+    }
     println!("Run in {} ms", now.elapsed().as_secs_f64() * 1000f64);
     println!("Statistic\n{}", res.1);
     match res.0 {
@@ -317,13 +595,48 @@ fn rand_solve(seed: &Seed, draw_step: NonZeroU8, start_seed: u64, terminated: &A
     }
 }
 
-fn test_graph(seed: &Seed, draw_step: NonZeroU8, path: &String, terminated: &Arc<AtomicBool>) {
+fn test_graph(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    path: &String,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) {
     let shuffled_deck = shuffle(seed);
 
     let g: Solitaire = Solitaire::new(&shuffled_deck, draw_step);
 
     let now = Instant::now();
-    let res = solver::run_graph(g, true, terminated);
+    let res = solver::run_graph(g, format == OutputFormat::Text, terminated);
+
+    if format == OutputFormat::Json {
+        let complete = matches!(res.0, Some((Control::Ok, _)));
+        if let Some((_, graph)) = res.0.as_ref().filter(|_| complete) {
+            let mut f = std::io::BufWriter::new(File::create(path).unwrap());
+            writeln!(f, "s,t,e,id").unwrap();
+            for (id, e) in graph.iter().skip(1).enumerate() {
+                writeln!(f, "{},{},{:?},{}", e.0, e.1, e.2, id).unwrap();
+            }
+        }
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "result": match &res.0 {
+                    None => "Crashed",
+                    Some(_) if complete => "Complete",
+                    Some(_) => "Unfinished",
+                },
+                "time_ms": now.elapsed().as_secs_f64() * 1000f64,
+                "stats": res.1.to_json(),
+                "edges": res.0.as_ref().map(|(_, graph)| graph.len()),
+                "out": complete.then_some(path),
+            })
+        );
+        return;
+    }
+
     println!("Run in {} ms", now.elapsed().as_secs_f64() * 1000f64);
     println!("Statistic\n{}", res.1);
     match res.0 {
@@ -344,53 +657,12 @@ fn test_graph(seed: &Seed, draw_step: NonZeroU8, path: &String, terminated: &Arc
     }
 }
 
-fn game_loop(seed: &Seed, draw_step: NonZeroU8) {
-    let shuffled_deck = shuffle(seed);
-
-    let mut game: SolitaireEngine<FullPruner> = Solitaire::new(&shuffled_deck, draw_step).into();
-
-    let mut line: String = String::new();
-
-    let mut game_state = HashSet::<Encode>::new();
-
-    loop {
-        print_game(game.state());
-        if !game_state.insert(game.encode()) {
-            println!("Already existed state");
-        }
-
-        let moves = game.list_moves_dom();
-
-        for (i, m) in moves.iter().enumerate() {
-            print!("{i}.{m}, ");
-        }
-        println!();
-
-        println!("Hash: {:?}", game.encode());
-        print!("Move: ");
-        std::io::stdout().flush().unwrap();
-        line.clear();
-        let b1 = std::io::stdin().read_line(&mut line);
-        if b1.is_err() {
-            println!("Can't read");
-            continue;
-        }
-        let res: Option<i8> = line.trim().parse::<i8>().ok();
-        if let Some(id) = res {
-            let id = usize::try_from(id).unwrap_or(usize::MAX);
-            if id < moves.len() {
-                assert!(game.do_move(moves[id]));
-            } else {
-                game.undo_move();
-                println!("Undo!!");
-            }
-        } else {
-            println!("Invalid move");
-        }
-    }
-}
-
-fn solve_loop(org_seed: &Seed, draw_step: NonZeroU8, terminated: &Arc<AtomicBool>) {
+fn solve_loop(
+    org_seed: &Seed,
+    draw_step: NonZeroU8,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) {
     let mut cnt_terminated = 0u32;
     let mut cnt_solve = 0u32;
     let mut cnt_total = 0u32;
@@ -420,21 +692,40 @@ fn solve_loop(org_seed: &Seed, draw_step: NonZeroU8, terminated: &Arc<AtomicBool
             .unwrap()
             .wilson_score(1.960)
             .upper(); //95%
-        println!(
-            "Run {} {:?}: ({}-{}/{} ~ {:.4}<={:.4}<={:.4}) {} {} {} in {:.2} ms.",
-            seed,
-            res,
-            cnt_solve,
-            cnt_terminated,
-            cnt_total,
-            lower,
-            f64::from(cnt_solve) / f64::from(cnt_total),
-            higher,
-            stats.total_visit(),
-            stats.unique_visit(),
-            stats.max_depth(),
-            now.elapsed().as_secs_f64() * 1000f64,
-        );
+        if format == OutputFormat::Json {
+            println!(
+                "{}",
+                json!({
+                    "seed": seed.to_string(),
+                    "draw_step": draw_step.get(),
+                    "result": res,
+                    "solved": cnt_solve,
+                    "terminated": cnt_terminated,
+                    "total": cnt_total,
+                    "lower": lower,
+                    "rate": f64::from(cnt_solve) / f64::from(cnt_total),
+                    "upper": higher,
+                    "stats": stats.to_json(),
+                    "time_ms": now.elapsed().as_secs_f64() * 1000f64,
+                })
+            );
+        } else {
+            println!(
+                "Run {} {:?}: ({}-{}/{} ~ {:.4}<={:.4}<={:.4}) {} {} {} in {:.2} ms.",
+                seed,
+                res,
+                cnt_solve,
+                cnt_terminated,
+                cnt_total,
+                lower,
+                f64::from(cnt_solve) / f64::from(cnt_total),
+                higher,
+                stats.total_visit(),
+                stats.unique_visit(),
+                stats.max_depth(),
+                now.elapsed().as_secs_f64() * 1000f64,
+            );
+        }
 
         if terminated.load(Ordering::Relaxed) {
             thread::sleep(Duration::from_millis(500));
@@ -465,19 +756,42 @@ fn handling_signal() -> Arc<AtomicBool> {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Print the exact permutation number and the deal code of the game
     Exact {
         #[command(flatten)]
         seed: StringSeed,
+        /// Draw step written in the deal code (default to the one of a given code, or 3)
+        #[arg(long)]
+        draw_step: Option<NonZeroU8>,
     },
     Print {
         #[command(flatten)]
         seed: StringSeed,
     },
 
+    /// Draw the game as an SVG image, after playing some moves
+    Render {
+        #[command(flatten)]
+        seed: StringSeed,
+        draw_step: NonZeroU8,
+        /// Standard moves to play before drawing, separated by spaces or commas
+        #[arg(long, default_value = "")]
+        moves: String,
+        /// Standard move drawn as an arrow
+        #[arg(long)]
+        highlight: Option<String>,
+        /// Output file (default to stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+
     Bench {
         #[command(flatten)]
         seed: StringSeed,
@@ -488,6 +802,115 @@ enum Commands {
         #[command(flatten)]
         seed: StringSeed,
         draw_step: NonZeroU8,
+        /// Save the search to this file when terminated (and periodically)
+        #[arg(long)]
+        checkpoint: Option<String>,
+        /// Continue the search saved in this file (and keep saving to it without `--checkpoint`)
+        #[arg(long)]
+        resume: Option<String>,
+        /// Write an HTML page stepping through the solution into this file
+        #[arg(long)]
+        report: Option<String>,
+    },
+
+    /// Check that the moves in a file (`-` for stdin) win the game
+    Verify {
+        #[command(flatten)]
+        seed: StringSeed,
+        draw_step: NonZeroU8,
+        path: String,
+        /// Notation of the moves (detected from the text by default)
+        #[arg(long, value_enum)]
+        notation: Option<verify::Notation>,
+    },
+
+    /// Split the search into subproblem files (`<prefix>.0`, `<prefix>.1`, ...)
+    Split {
+        #[command(flatten)]
+        seed: StringSeed,
+        draw_step: NonZeroU8,
+        depth: usize,
+        n_files: usize,
+        prefix: String,
+    },
+
+    /// Solve a subproblem file created by `split` and write the result into `out`
+    SolvePart { path: String, out: String },
+
+    /// Merge the result files of `solve-part`
+    Merge { paths: Vec<String> },
+
+    /// Estimate the size of the search tree
+    Estimate {
+        #[command(flatten)]
+        seed: StringSeed,
+        draw_step: NonZeroU8,
+        #[arg(default_value_t = 1000)]
+        n_probes: usize,
+    },
+
+    /// Rate the difficulty of the game
+    Difficulty {
+        #[command(flatten)]
+        seed: StringSeed,
+        draw_step: NonZeroU8,
+        /// Maximum number of unique states for each search
+        #[arg(long, default_value_t = 1_000_000)]
+        max_states: usize,
+        /// Number of HOP samples for the win rate without knowing the hidden cards (0 to skip)
+        #[arg(long, default_value_t = 0)]
+        hop: usize,
+    },
+
+    /// Scan a range of seeds for the deals matching the filters
+    Mine {
+        #[command(flatten)]
+        seed: StringSeed,
+        draw_step: NonZeroU8,
+        #[command(flatten)]
+        options: mine::MineOptions,
+    },
+
+    /// Solve the deals listed in a file (seeds or Solvitaire JSON) and write one JSON line per deal
+    Batch {
+        path: String,
+        draw_step: NonZeroU8,
+        /// Time limit for each deal in seconds
+        #[arg(long, default_value_t = 60.)]
+        timeout: f64,
+        /// Number of threads (default to the number of cores)
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Output file (default to stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+
+    /// Solve the Klondike games found in a Microsoft Solitaire Collection log file (`solitaire.log`)
+    ImportMscl {
+        path: String,
+        /// Draw step of the games whose draw mode is not in the log
+        #[arg(long, default_value = "3")]
+        draw_step: NonZeroU8,
+        /// Only list the games found in the log
+        #[arg(long)]
+        list: bool,
+        /// Time limit for each deal in seconds
+        #[arg(long, default_value_t = 60.)]
+        timeout: f64,
+        /// Number of threads (default to the number of cores)
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Output file (default to stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+
+    /// Find the seeds whose deal has the known cards, by trying all of them
+    FindSeed {
+        seed_type: SeedType,
+        #[command(flatten)]
+        options: find_seed::FindSeedOptions,
     },
 
     RandSolve {
@@ -504,6 +927,7 @@ enum Commands {
         out: String,
     },
 
+    /// Play the game in the terminal, with undo, hints and the solver telling if it's still winnable
     Play {
         #[command(flatten)]
         seed: StringSeed,
@@ -534,43 +958,315 @@ enum Commands {
     },
 }
 
+impl Commands {
+    /// The seed and the draw step of the commands taking both
+    fn seed_and_draw_step(&self) -> Option<(&StringSeed, NonZeroU8)> {
+        match self {
+            Self::Exact {
+                seed,
+                draw_step: Some(draw_step),
+            }
+            | Self::Render {
+                seed, draw_step, ..
+            }
+            | Self::Bench { seed, draw_step }
+            | Self::Solve {
+                seed, draw_step, ..
+            }
+            | Self::Verify {
+                seed, draw_step, ..
+            }
+            | Self::Split {
+                seed, draw_step, ..
+            }
+            | Self::Estimate {
+                seed, draw_step, ..
+            }
+            | Self::Difficulty {
+                seed, draw_step, ..
+            }
+            | Self::Mine {
+                seed, draw_step, ..
+            }
+            | Self::RandSolve {
+                seed, draw_step, ..
+            }
+            | Self::Graph {
+                seed, draw_step, ..
+            }
+            | Self::Play { seed, draw_step }
+            | Self::Random { seed, draw_step }
+            | Self::Rate { seed, draw_step }
+            | Self::Hop { seed, draw_step }
+            | Self::HopLoop { seed, draw_step } => Some((seed, *draw_step)),
+            _ => None,
+        }
+    }
+
+    /// The name of the commands without a JSON output
+    const fn without_json(&self) -> Option<&'static str> {
+        match self {
+            Self::Render { .. } => Some("render"),
+            Self::Split { .. } => Some("split"),
+            Self::SolvePart { .. } => Some("solve-part"),
+            Self::Merge { .. } => Some("merge"),
+            Self::Play { .. } => Some("play"),
+            _ => None,
+        }
+    }
+}
+
+/// A deal code is only played with the draw step written in it
+fn check_draw_step(args: &Commands) -> Result<(), String> {
+    let Some((seed, draw_step)) = args.seed_and_draw_step() else {
+        return Ok(());
+    };
+    match Seed::code_draw_step(seed.seed_type, &seed.seed) {
+        Some(code_step) if code_step != draw_step => Err(format!(
+            "the deal code is for draw {code_step}, not draw {draw_step}"
+        )),
+        _ => Ok(()),
+    }
+}
+
 fn main() {
-    let args = Cli::parse().command;
+    let cli = Cli::parse();
+    let (args, format) = (cli.command, cli.format);
+    if let Err(err) = check_draw_step(&args) {
+        eprintln!("{err}");
+        return;
+    }
+    if let Some(name) = args.without_json().filter(|_| format == OutputFormat::Json) {
+        eprintln!("{name} has no JSON output");
+        return;
+    }
 
     match &args {
         Commands::Print { seed } => {
-            let shuffled_deck = shuffle(&seed.into());
+            let seed: Seed = seed.into();
+            let shuffled_deck = shuffle(&seed);
             let g = StandardSolitaire::new(&shuffled_deck, NonZeroU8::MIN);
 
-            println!("{}", Solvitaire(g));
+            if format == OutputFormat::Json {
+                let deal: Value = serde_json::from_str(&Solvitaire(g).to_string()).unwrap();
+                println!("{}", json!({"seed": seed.to_string(), "deal": deal}));
+            } else {
+                println!("{}", Solvitaire(g));
+            }
         }
-        Commands::Solve { seed, draw_step } => {
-            test_solve(&seed.into(), *draw_step, &handling_signal());
+        Commands::Render {
+            seed,
+            draw_step,
+            moves,
+            highlight,
+            out,
+        } => {
+            if let Err(err) = render::render(
+                &seed.into(),
+                *draw_step,
+                moves,
+                highlight.as_deref(),
+                out.as_deref(),
+            ) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::Solve {
+            seed,
+            draw_step,
+            checkpoint,
+            resume,
+            report,
+        } => {
+            test_solve(
+                &seed.into(),
+                *draw_step,
+                checkpoint.as_deref(),
+                resume.as_deref(),
+                report.as_deref(),
+                format,
+                &handling_signal(),
+            );
+        }
+        Commands::Verify {
+            seed,
+            draw_step,
+            path,
+            notation,
+        } => {
+            if let Err(err) = verify::verify(&seed.into(), *draw_step, path, *notation, format) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::Split {
+            seed,
+            draw_step,
+            depth,
+            n_files,
+            prefix,
+        } => {
+            if let Err(err) =
+                split::write_parts(shuffle(&seed.into()), *draw_step, *depth, *n_files, prefix)
+            {
+                eprintln!("{err}");
+            }
+        }
+        Commands::SolvePart { path, out } => {
+            if let Err(err) = split::solve_part(path, out, &handling_signal()) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::Merge { paths } => {
+            if let Err(err) = split::merge(paths) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::Estimate {
+            seed,
+            draw_step,
+            n_probes,
+        } => do_estimate(&seed.into(), *draw_step, *n_probes, format),
+        Commands::Difficulty {
+            seed,
+            draw_step,
+            max_states,
+            hop,
+        } => do_difficulty(
+            &seed.into(),
+            *draw_step,
+            &DifficultyConfig {
+                max_states: *max_states,
+                hop_samples: *hop,
+                ..Default::default()
+            },
+            format,
+            &handling_signal(),
+        ),
+        Commands::Mine {
+            seed,
+            draw_step,
+            options,
+        } => {
+            if let Err(err) = mine::mine(
+                &seed.into(),
+                *draw_step,
+                options,
+                format,
+                &handling_signal(),
+            ) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::Batch {
+            path,
+            draw_step,
+            timeout,
+            threads,
+            out,
+        } => {
+            if let Err(err) = batch::batch(
+                path,
+                *draw_step,
+                Duration::from_secs_f64(*timeout),
+                *threads,
+                out.as_deref(),
+                &handling_signal(),
+            ) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::ImportMscl {
+            path,
+            draw_step,
+            list,
+            timeout,
+            threads,
+            out,
+        } => {
+            if let Err(err) = mscl::import_mscl(
+                path,
+                *draw_step,
+                *list,
+                Duration::from_secs_f64(*timeout),
+                *threads,
+                out.as_deref(),
+                format,
+                &handling_signal(),
+            ) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::FindSeed { seed_type, options } => {
+            if let Err(err) = find_seed::find_seed(*seed_type, options, format, &handling_signal())
+            {
+                eprintln!("{err}");
+            }
         }
         Commands::RandSolve {
             seed,
             draw_step,
             start_seed,
         } => {
-            rand_solve(&seed.into(), *draw_step, *start_seed, &handling_signal());
+            rand_solve(
+                &seed.into(),
+                *draw_step,
+                *start_seed,
+                format,
+                &handling_signal(),
+            );
         }
         Commands::Graph {
             seed,
             draw_step,
             out,
-        } => test_graph(&seed.into(), *draw_step, out, &handling_signal()),
-        Commands::Play { seed, draw_step } => game_loop(&seed.into(), *draw_step),
-        Commands::Bench { seed, draw_step } => benchmark(&seed.into(), *draw_step),
+        } => test_graph(&seed.into(), *draw_step, out, format, &handling_signal()),
+        Commands::Play { seed, draw_step } => {
+            if let Err(err) = tui::play(&seed.into(), *draw_step) {
+                eprintln!("{err}");
+            }
+        }
+        Commands::Bench { seed, draw_step } => benchmark(&seed.into(), *draw_step, format),
         Commands::Rate { seed, draw_step } => {
-            solve_loop(&seed.into(), *draw_step, &handling_signal());
+            solve_loop(&seed.into(), *draw_step, format, &handling_signal());
         }
-        Commands::Exact { seed } => {
-            let shuffled_deck = shuffle(&seed.into());
-            println!("{}", shuffler::encode_shuffle(shuffled_deck).unwrap());
+        Commands::Exact { seed, draw_step } => {
+            let draw_step = draw_step
+                .or_else(|| Seed::code_draw_step(seed.seed_type, &seed.seed))
+                .unwrap_or(NonZeroU8::new(3).unwrap());
+            let seed: Seed = seed.into();
+            let code = DealCode::new(shuffle(&seed), draw_step).unwrap();
+            if format == OutputFormat::Json {
+                println!(
+                    "{}",
+                    json!({
+                        "seed": seed.to_string(),
+                        "exact": code.exact.to_string(),
+                        "code": code.to_string(),
+                    })
+                );
+            } else {
+                println!("{}", code.exact);
+                println!("{code}");
+            }
         }
-        Commands::Random { seed, draw_step } => do_random(&seed.into(), *draw_step),
+        Commands::Random { seed, draw_step } => do_random(&seed.into(), *draw_step, format),
         Commands::Hop { seed, draw_step } => {
-            do_hop(&seed.into(), *draw_step, true);
+            let seed: Seed = seed.into();
+            let (won, moves) = do_hop(&seed, *draw_step, format == OutputFormat::Text);
+            if format == OutputFormat::Json {
+                let game = Solitaire::new(&shuffle(&seed), *draw_step);
+                let standard = convert_moves(&mut StandardSolitaire::from(&game), &moves).ok();
+                println!(
+                    "{}",
+                    json!({
+                        "seed": seed.to_string(),
+                        "draw_step": draw_step.get(),
+                        "result": if won { "Solved" } else { "Lost" },
+                        "moves": moves,
+                        "standard_moves": standard.as_ref().map(|m| m.as_slice()),
+                    })
+                );
+            }
         }
         Commands::HopLoop { seed, draw_step } => {
             let mut cnt_solve: u32 = 0;
@@ -578,12 +1274,30 @@ fn main() {
                 let s: Seed = seed.into();
                 let start = time::Instant::now();
 
-                cnt_solve += u32::from(do_hop(&s.increase(i), *draw_step, false));
+                let won = do_hop(&s.increase(i), *draw_step, false).0;
+                cnt_solve += u32::from(won);
                 let elapsed = start.elapsed();
 
                 let interval = NSuccessesSample::new(i + 1, cnt_solve)
                     .unwrap()
                     .wilson_score(1.960);
+                if format == OutputFormat::Json {
+                    println!(
+                        "{}",
+                        json!({
+                            "seed": s.increase(i).to_string(),
+                            "draw_step": draw_step.get(),
+                            "result": if won { "Solved" } else { "Lost" },
+                            "solved": cnt_solve,
+                            "total": i + 1,
+                            "lower": interval.lower(),
+                            "rate": f64::from(cnt_solve) / f64::from(i + 1),
+                            "upper": interval.upper(),
+                            "time_ms": elapsed.as_secs_f64() * 1000f64,
+                        })
+                    );
+                    continue;
+                }
                 println!(
                     "{}/{} ~ {:.4} < {:.4} < {:.4} in {:?}",
                     cnt_solve,
@@ -597,3 +1311,44 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_draw_step() {
+        let code = DealCode::new(shuffler::default_shuffle(12), NonZeroU8::new(1).unwrap())
+            .unwrap()
+            .to_string();
+        let args = |draw_step: &str| {
+            Cli::try_parse_from(["lonecli", "solve", "code", &code, draw_step])
+                .unwrap()
+                .command
+        };
+        assert!(check_draw_step(&args("1")).is_ok());
+        assert_eq!(
+            check_draw_step(&args("3")),
+            Err("the deal code is for draw 1, not draw 3".to_string())
+        );
+
+        // the other seed types have no draw step of their own
+        let args = Cli::try_parse_from(["lonecli", "solve", "default", "12", "3"]).unwrap();
+        assert!(check_draw_step(&args.command).is_ok());
+        let args = Cli::try_parse_from(["lonecli", "exact", "code", &code]).unwrap();
+        assert!(check_draw_step(&args.command).is_ok());
+    }
+
+    #[test]
+    fn test_without_json() {
+        let args = |args: &[&str]| Cli::try_parse_from(args).unwrap().command;
+        assert_eq!(
+            args(&["lonecli", "play", "default", "12", "3", "--format", "json"]).without_json(),
+            Some("play")
+        );
+        assert_eq!(
+            args(&["lonecli", "difficulty", "default", "12", "3"]).without_json(),
+            None
+        );
+    }
+}
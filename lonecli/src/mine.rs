@@ -0,0 +1,282 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    num::NonZeroU8,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        mpsc::channel,
+        Arc,
+    },
+    thread,
+};
+
+use clap::Args;
+use lonelybot::{
+    difficulty::{count_redeals, rate_difficulty, DifficultyConfig, Tier},
+    solver::{solve_with_tracking, SearchResult},
+    state::Solitaire,
+    tracking::{StateLimit, TerminateSignal},
+};
+use rand::{rngs::SmallRng, SeedableRng};
+use serde_json::json;
+
+use crate::{solver::STACK_SIZE, try_shuffle, OutputFormat, Seed};
+
+#[derive(Args, Clone)]
+pub(crate) struct MineOptions {
+    /// Number of seeds to scan
+    count: u32,
+    #[command(flatten)]
+    filter: MineFilter,
+    /// Maximum number of unique states for each search
+    #[arg(long, default_value_t = 1_000_000)]
+    max_states: usize,
+    /// Number of threads (default to the number of cores)
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Output file (default to stdout)
+    #[arg(long)]
+    out: Option<String>,
+    /// Write CSV with a header line instead of the `--format` output
+    #[arg(long)]
+    csv: bool,
+}
+
+#[derive(Args, Clone)]
+pub(crate) struct MineFilter {
+    /// Only keep the solvable deals
+    #[arg(long)]
+    solvable: bool,
+    /// Only keep the deals proven unsolvable
+    #[arg(long)]
+    unsolvable: bool,
+    /// Minimum length of the solution found by the solver
+    #[arg(long)]
+    min_length: Option<usize>,
+    /// Difficulty tier (easy, medium, hard, expert)
+    #[arg(long)]
+    tier: Option<Tier>,
+}
+
+struct Record {
+    seed: String,
+    result: SearchResult,
+    states: usize,
+    solution_len: Option<usize>,
+    redeals: Option<usize>,
+    tier: Option<Tier>,
+}
+
+impl Record {
+    fn write(&self, out: &mut impl Write, csv: bool, format: OutputFormat) -> io::Result<()> {
+        if csv {
+            return writeln!(
+                out,
+                "{},{:?},{},{},{},{}",
+                self.seed,
+                self.result,
+                self.states,
+                self.solution_len.map_or(String::new(), |x| x.to_string()),
+                self.redeals.map_or(String::new(), |x| x.to_string()),
+                self.tier.map_or(String::new(), |x| x.to_string()),
+            );
+        }
+        match format {
+            OutputFormat::Text => {
+                write!(
+                    out,
+                    "{}: {:?}, {} states",
+                    self.seed, self.result, self.states
+                )?;
+                if let Some(len) = self.solution_len {
+                    write!(out, ", {len} moves")?;
+                }
+                if let Some(redeals) = self.redeals {
+                    write!(out, ", {redeals} redeals")?;
+                }
+                if let Some(tier) = self.tier {
+                    write!(out, ", {tier}")?;
+                }
+                writeln!(out)
+            }
+            OutputFormat::Json => writeln!(
+                out,
+                "{}",
+                json!({
+                    "seed": self.seed,
+                    "result": self.result,
+                    "states": self.states,
+                    "solution_len": self.solution_len,
+                    "redeals": self.redeals,
+                    "tier": self.tier.map(|x| x.to_string()),
+                })
+            ),
+        }
+    }
+}
+
+struct Signal<'a>(&'a AtomicBool);
+
+impl TerminateSignal for Signal<'_> {
+    fn is_terminated(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+fn check(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    filter: &MineFilter,
+    max_states: usize,
+    sign: &Signal,
+) -> Option<Record> {
+    // the range can go past the seeds accepted by the seed type
+    let game = Solitaire::new(&try_shuffle(seed)?, draw_step);
+
+    let (result, states, solution_len, redeals, tier) = if filter.tier.is_some() {
+        let mut rng = SmallRng::seed_from_u64(seed.seed().low_u64());
+        let report = rate_difficulty(
+            &game,
+            &DifficultyConfig {
+                max_states,
+                ..Default::default()
+            },
+            &mut rng,
+            sign,
+        );
+        (
+            report.result,
+            report.unique_states,
+            report.found_solution_len,
+            report.redeals,
+            Some(report.tier),
+        )
+    } else {
+        let limit = StateLimit::new(max_states, sign);
+        let (result, history) = solve_with_tracking(&mut game.clone(), &limit, &limit);
+        (
+            result,
+            limit.states(),
+            history.as_ref().map(|h| h.len()),
+            history.as_ref().and_then(|h| count_redeals(&game, h)),
+            None,
+        )
+    };
+
+    if sign.is_terminated() {
+        return None;
+    }
+
+    let matched = (!filter.solvable || result == SearchResult::Solved)
+        && (!filter.unsolvable || result == SearchResult::Unsolvable)
+        && filter
+            .min_length
+            .is_none_or(|x| solution_len.is_some_and(|len| len >= x))
+        && filter.tier.is_none_or(|x| tier == Some(x));
+
+    matched.then(|| Record {
+        seed: seed.to_string(),
+        result,
+        states,
+        solution_len,
+        redeals,
+        tier,
+    })
+}
+
+/// Scan the seeds starting from `seed` and write the deals matching the filter
+pub(crate) fn mine(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    options: &MineOptions,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let mut out: Box<dyn Write> = match &options.out {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let count = options.count;
+    let n_threads = options
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+
+    if options.csv {
+        writeln!(out, "seed,result,states,solution_len,redeals,tier")?;
+    }
+
+    let next = AtomicU32::new(0);
+    let (send, recv) = channel::<Record>();
+
+    thread::scope(|s| {
+        for _ in 0..n_threads.max(1) {
+            let send = send.clone();
+            let next = &next;
+            thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(s, move || {
+                    let sign = Signal(terminated.as_ref());
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= count || sign.is_terminated() {
+                            break;
+                        }
+                        if let Some(record) = check(
+                            &seed.increase(i),
+                            draw_step,
+                            &options.filter,
+                            options.max_states,
+                            &sign,
+                        ) {
+                            send.send(record).ok();
+                        }
+                    }
+                })
+                .unwrap();
+        }
+        drop(send);
+
+        let mut n_found = 0;
+        for record in recv {
+            record.write(&mut out, options.csv, format)?;
+            out.flush()?;
+            n_found += 1;
+        }
+        eprintln!(
+            "Found {n_found} deals in {} seeds",
+            next.load(Ordering::Relaxed).min(count)
+        );
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_write() {
+        let record = Record {
+            seed: "L-12".to_string(),
+            result: SearchResult::Solved,
+            states: 1234,
+            solution_len: Some(110),
+            redeals: Some(4),
+            tier: None,
+        };
+        let write = |csv, format| {
+            let mut out = Vec::new();
+            record.write(&mut out, csv, format).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            write(false, OutputFormat::Text),
+            "L-12: Solved, 1234 states, 110 moves, 4 redeals\n"
+        );
+        assert_eq!(write(true, OutputFormat::Text), "L-12,Solved,1234,110,4,\n");
+        let json: serde_json::Value =
+            serde_json::from_str(&write(false, OutputFormat::Json)).unwrap();
+        assert_eq!(json["solution_len"], 110);
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    num::NonZeroU8,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
+
+use lonelybot::shuffler::{microsoft_shuffle, U256};
+use serde_json::json;
+
+use crate::{
+    batch::{solve_deals, Deal},
+    OutputFormat, Seed, SeedType,
+};
+
+// the lines of `solitaire.log` which carry the seed of a game
+const MARKERS: [&str; 2] = [
+    "SolitaireGameLogic::TryLoadGame",
+    "SolvableDeckAppComponent",
+];
+// the other games of the collection have their own dealers
+const OTHER_GAMES: [&str; 4] = ["spider", "freecell", "pyramid", "tripeaks"];
+
+#[derive(Debug)]
+pub(crate) struct LogGame {
+    /// Line number (from 1) of the first line mentioning the game
+    pub(crate) line: usize,
+    pub(crate) timestamp: Option<String>,
+    pub(crate) seed: U256,
+    pub(crate) draw_step: Option<NonZeroU8>,
+}
+
+// the leading tokens with digits, e.g. `2024-03-10 18:22:31.123` in `[2024-03-10 18:22:31.123] [Info]`
+fn parse_timestamp(prefix: &str) -> Option<String> {
+    let tokens: Vec<&str> = prefix
+        .split(|c: char| c.is_whitespace() || "[]|".contains(c))
+        .filter(|x| !x.is_empty())
+        .take_while(|x| x.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|x| x.trim_end_matches([':', ',']))
+        .collect();
+    (!tokens.is_empty()).then(|| tokens.join(" "))
+}
+
+// the seed field is `seed` or `deck` followed by the decimal seed, with a space, `:` or `=`
+// in between (`seed 123`, `Seed: 123`, `Seed=123`, `deck 123`), only one seed per line
+fn parse_seed(text: &str) -> Result<U256, String> {
+    const KEYS: [&str; 2] = ["seed", "deck"];
+
+    let tokens: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == '|')
+        .filter(|x| !x.is_empty())
+        .collect();
+
+    let mut seed = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let (key, value) = match tokens[i].split_once(['=', ':']) {
+            Some((key, value)) if !value.is_empty() => (key, Some(value)),
+            Some((key, _)) => (key, None),
+            None => (tokens[i], None),
+        };
+        i += 1;
+        if !KEYS.iter().any(|k| key.eq_ignore_ascii_case(k)) {
+            continue;
+        }
+        let value = match value {
+            Some(value) => value,
+            None => {
+                let value = tokens.get(i).ok_or("missing seed value")?;
+                i += 1;
+                value
+            }
+        };
+        let value = value.trim_end_matches([',', ';']);
+        if value.is_empty() || !value.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(format!("invalid seed `{value}`"));
+        }
+        let value =
+            U256::from_dec_str(value).map_err(|_| format!("seed `{value}` is too large"))?;
+        if seed.is_some_and(|x| x != value) {
+            return Err("several seeds".to_string());
+        }
+        seed = Some(value);
+    }
+    seed.ok_or_else(|| "no seed field".to_string())
+}
+
+// `Draw3`, `draw: 1`, `DrawThree`, `three card`, ... ignoring the case and the punctuation
+fn parse_draw(text: &str) -> Option<NonZeroU8> {
+    const PATTERNS: [(&str, u8); 10] = [
+        ("drawthree", 3),
+        ("drawcount3", 3),
+        ("draw3", 3),
+        ("threecard", 3),
+        ("3card", 3),
+        ("drawone", 1),
+        ("drawcount1", 1),
+        ("draw1", 1),
+        ("onecard", 1),
+        ("1card", 1),
+    ];
+
+    let compact: String = text
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    PATTERNS.iter().find_map(|&(pattern, step)| {
+        // `draw1` shouldn't match the beginning of `draw12345`
+        let found = compact
+            .match_indices(pattern)
+            .any(|(i, _)| !compact[i + pattern.len()..].starts_with(|c: char| c.is_ascii_digit()));
+        found.then(|| NonZeroU8::new(step).unwrap())
+    })
+}
+
+/// Extract the Klondike games from the content of a Microsoft Solitaire Collection log file
+///
+/// The games are listed once each (in the order of their first line).
+/// The marker lines without a valid seed field or with a seed rejected by `microsoft_shuffle`
+/// are returned as errors, one per line
+pub(crate) fn parse_log(content: &str) -> (Vec<LogGame>, Vec<String>) {
+    let mut games: Vec<LogGame> = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = HashMap::new();
+
+    for (line_no, line) in content.lines().enumerate() {
+        let Some(pos) = MARKERS.iter().filter_map(|m| line.find(m)).min() else {
+            continue;
+        };
+        let rest = &line[pos..];
+        let lower = rest.to_ascii_lowercase();
+        if OTHER_GAMES.iter().any(|g| lower.contains(g)) {
+            continue;
+        }
+
+        let seed = match parse_seed(rest) {
+            Ok(seed) if microsoft_shuffle(seed).is_some() => seed,
+            Ok(seed) => {
+                errors.push(format!(
+                    "line {}: seed {seed} is rejected by the microsoft dealer",
+                    line_no + 1
+                ));
+                continue;
+            }
+            Err(err) => {
+                errors.push(format!("line {}: {err}", line_no + 1));
+                continue;
+            }
+        };
+        let draw_step = parse_draw(rest);
+
+        // the same game is usually logged by several components
+        if let Some(&i) = seen.get(&seed) {
+            let game: &mut LogGame = &mut games[i];
+            game.draw_step = game.draw_step.or(draw_step);
+            continue;
+        }
+        seen.insert(seed, games.len());
+        games.push(LogGame {
+            line: line_no + 1,
+            timestamp: parse_timestamp(&line[..pos]),
+            seed,
+            draw_step,
+        });
+    }
+    (games, errors)
+}
+
+/// Solve all the games found in the log file at `path`, or only list them with `list`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn import_mscl(
+    path: &str,
+    draw_step: NonZeroU8,
+    list: bool,
+    timeout: Duration,
+    threads: Option<usize>,
+    out: Option<&str>,
+    format: OutputFormat,
+    terminated: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    // the log can contain some invalid UTF-8
+    let content = String::from_utf8_lossy(&fs::read(path)?).into_owned();
+    let (games, errors) = parse_log(&content);
+    for err in &errors {
+        eprintln!("{err}");
+    }
+    eprintln!("Found {} games in {path}", games.len());
+
+    let seed = |game: &LogGame| Seed {
+        seed_type: SeedType::Microsoft,
+        seed: game.seed,
+    };
+
+    if list {
+        for game in &games {
+            let draw = game.draw_step.map(NonZeroU8::get);
+            if format == OutputFormat::Json {
+                println!(
+                    "{}",
+                    json!({
+                        "line": game.line,
+                        "timestamp": game.timestamp,
+                        "seed": seed(game).to_string(),
+                        "draw_step": draw,
+                    })
+                );
+            } else {
+                println!(
+                    "{}\t{}\t{}\tdraw {}",
+                    game.line,
+                    game.timestamp.as_deref().unwrap_or("-"),
+                    seed(game),
+                    draw.map_or_else(|| "?".to_string(), |x| x.to_string()),
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let deals: Vec<Deal> = games
+        .iter()
+        .map(|game| Deal {
+            name: seed(game).to_string(),
+            deck: microsoft_shuffle(game.seed).unwrap(),
+            draw_step: game.draw_step,
+            timestamp: game.timestamp.clone(),
+        })
+        .collect();
+    solve_deals(&deals, draw_step, timeout, threads, out, terminated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed("seed 123"), Ok(U256::from(123)));
+        assert_eq!(parse_seed("Seed: 123, Draw: 1"), Ok(U256::from(123)));
+        assert_eq!(parse_seed("| Seed=123 |"), Ok(U256::from(123)));
+        assert_eq!(parse_seed("picked deck 123 (level 4)"), Ok(U256::from(123)));
+        assert_eq!(
+            parse_seed("deck 12 seed 34"),
+            Err("several seeds".to_string())
+        );
+        assert_eq!(parse_seed("game 123"), Err("no seed field".to_string()));
+        assert_eq!(parse_seed("seed"), Err("missing seed value".to_string()));
+        assert_eq!(
+            parse_seed("seed 12ab"),
+            Err("invalid seed `12ab`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_log() {
+        // one line for each accepted layout and for each kind of rejected line
+        let log = "\
+[2024-03-10 18:22:31.123] [Info] SolitaireGameLogic::TryLoadGame: Klondike DrawThree seed 1234567890
+[2024-03-10 18:22:31.456] [Info] SolvableDeckAppComponent: picked deck 1234567890
+[2024-03-10 18:25:02.001] [Info] SolitaireGameLogic::TryLoadGame: Spider seed 42
+2024-03-10T18:30:00Z | SolvableDeckAppComponent | Klondike Draw: 1 Seed=987654321
+[2024-03-10 18:31:00.000] [Info] SolvableDeckAppComponent: no deck
+SolitaireGameLogic::TryLoadGame 31415926535 draw 3
+SolitaireGameLogic::TryLoadGame seed 99999999999999999999999999999999999999999999999999999999999999999999999
+SolitaireGameLogic::TryLoadGame: Klondike DrawOne seed 2718281828 (level 12)
+";
+        let (games, errors) = parse_log(log);
+        assert_eq!(games.len(), 3);
+
+        assert_eq!(games[0].line, 1);
+        assert_eq!(
+            games[0].timestamp.as_deref(),
+            Some("2024-03-10 18:22:31.123")
+        );
+        assert_eq!(games[0].seed, U256::from(1_234_567_890u64));
+        assert_eq!(games[0].draw_step.map(NonZeroU8::get), Some(3));
+
+        assert_eq!(games[1].line, 4);
+        assert_eq!(games[1].timestamp.as_deref(), Some("2024-03-10T18:30:00Z"));
+        assert_eq!(games[1].seed, U256::from(987_654_321u64));
+        assert_eq!(games[1].draw_step.map(NonZeroU8::get), Some(1));
+
+        assert_eq!(games[2].line, 8);
+        assert_eq!(games[2].timestamp, None);
+        assert_eq!(games[2].seed, U256::from(2_718_281_828u64));
+        assert_eq!(games[2].draw_step.map(NonZeroU8::get), Some(1));
+
+        // a bare number isn't a seed field
+        assert_eq!(
+            errors,
+            [
+                "line 5: missing seed value",
+                "line 6: no seed field",
+                "line 7: seed 99999999999999999999999999999999999999999999999999999999999999999999999 is rejected by the microsoft dealer",
+            ]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fs, io, num::NonZeroU8};
+
+use lonelybot::{
+    standard::{StandardMove, StandardSolitaire},
+    svg::to_svg,
+};
+
+use crate::{shuffle, Seed};
+
+fn invalid(err: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err)
+}
+
+// play the standard moves (separated by spaces or commas) from the start of the game
+fn replay(game: &mut StandardSolitaire, moves: &str) -> io::Result<()> {
+    for (i, token) in moves
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|x| !x.is_empty())
+        .enumerate()
+    {
+        let m: StandardMove = token
+            .parse()
+            .map_err(|_| invalid(format!("invalid move #{} `{token}`", i + 1)))?;
+        if game.do_move(&m).is_err() {
+            return Err(invalid(format!("illegal move #{} `{token}`", i + 1)));
+        }
+    }
+    Ok(())
+}
+
+/// Write the position after `moves` as an SVG image into `out` (default to stdout)
+pub(crate) fn render(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    moves: &str,
+    highlight: Option<&str>,
+    out: Option<&str>,
+) -> io::Result<()> {
+    let mut game = StandardSolitaire::new(&shuffle(seed), draw_step);
+    replay(&mut game, moves)?;
+
+    let highlight = highlight
+        .map(|x| {
+            let m: StandardMove = x
+                .parse()
+                .map_err(|_| invalid(format!("invalid move `{x}`")))?;
+            if game.validate_move(&m) {
+                Ok(m)
+            } else {
+                Err(invalid(format!("illegal move `{x}`")))
+            }
+        })
+        .transpose()?;
+
+    let svg = to_svg(&game, highlight);
+    match out {
+        Some(path) => fs::write(path, svg),
+        None => {
+            print!("{svg}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lonelybot::shuffler::default_shuffle;
+
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        let draw_step = NonZeroU8::new(3).unwrap();
+        let mut game = StandardSolitaire::new(&default_shuffle(12), draw_step);
+        let draw = StandardMove::DRAW_NEXT.to_string();
+        replay(&mut game, &format!("{draw}, {draw}")).unwrap();
+        assert_eq!(game.get_deck().waste_iter().len(), 6);
+
+        let mut game = StandardSolitaire::new(&default_shuffle(12), draw_step);
+        assert!(replay(&mut game, "XX").is_err());
+    }
+}
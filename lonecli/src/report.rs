@@ -0,0 +1,217 @@
+use std::{fmt::Write as _, fs, io, num::NonZeroU8, time::Duration};
+
+use lonelybot::{
+    convert::convert_moves, deal_code::DealCode, moves::Move, shuffler::CardDeck,
+    solver::SearchResult, standard::StandardSolitaire, svg::to_svg,
+};
+use serde_json::{json, Value};
+
+use crate::{tracking::AtomicSearchStats, Seed};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 16px; background: #f4f4f4; color: #222; }
+table { border-collapse: collapse; margin-bottom: 12px; }
+td { padding: 2px 12px 2px 0; }
+td:first-child { color: #666; }
+#controls { margin: 8px 0; }
+#controls button { font-size: 16px; min-width: 40px; }
+#step { font-size: 18px; margin: 8px 0; }
+#board svg { max-width: 100%; height: auto; }
+code { font-size: 18px; }";
+
+const SCRIPT: &str = "\
+let current = 0;
+const show = (i) => {
+  current = Math.max(0, Math.min(STEPS.length - 1, i));
+  const step = STEPS[current];
+  document.getElementById('board').innerHTML = step.svg;
+  document.getElementById('step').innerHTML = step.text;
+  document.getElementById('slider').value = current;
+  document.getElementById('counter').textContent = current + ' / ' + (STEPS.length - 1);
+};
+const slider = document.getElementById('slider');
+slider.max = STEPS.length - 1;
+slider.oninput = () => show(Number(slider.value));
+document.getElementById('first').onclick = () => show(0);
+document.getElementById('back').onclick = () => show(current - 1);
+document.getElementById('next').onclick = () => show(current + 1);
+document.getElementById('last').onclick = () => show(STEPS.length - 1);
+document.onkeydown = (e) => {
+  if (e.key === 'ArrowLeft') show(current - 1);
+  if (e.key === 'ArrowRight') show(current + 1);
+  if (e.key === 'Home') show(0);
+  if (e.key === 'End') show(STEPS.length - 1);
+};
+show(0);";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// the board before each standard move with the move drawn on it, then the final board
+fn steps(deck: &CardDeck, draw_step: NonZeroU8, moves: &[Move]) -> Vec<Value> {
+    let mut game = StandardSolitaire::new(deck, draw_step);
+    // converting a move plays it, so it's done on a second copy of the game
+    let mut ahead = StandardSolitaire::new(deck, draw_step);
+    let mut steps = Vec::new();
+    let mut n_standard = 0;
+
+    for (i, &m) in moves.iter().enumerate() {
+        let Ok(standard) = convert_moves(&mut ahead, &[m]) else {
+            break;
+        };
+        for sm in &standard {
+            n_standard += 1;
+            steps.push(json!({
+                "svg": to_svg(&game, Some(*sm)),
+                "text": format!(
+                    "Move {n_standard}: <code>{}</code> &mdash; solver move {}/{}: <code>{}</code>",
+                    escape(&sm.to_string()),
+                    i + 1,
+                    moves.len(),
+                    escape(&m.to_string()),
+                ),
+            }));
+            game.do_move(sm).unwrap();
+        }
+    }
+    steps.push(json!({
+        "svg": to_svg(&game, None),
+        "text": if game.is_win() { "Won" } else { "End" },
+    }));
+    steps
+}
+
+/// A single HTML page stepping through the solution, with the search summary on top
+///
+/// With `resumed`, the statistics and the time only cover the search since it was resumed
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn report_html(
+    seed: &Seed,
+    deck: &CardDeck,
+    draw_step: NonZeroU8,
+    result: SearchResult,
+    stats: &AtomicSearchStats,
+    resumed: bool,
+    elapsed: Duration,
+    moves: Option<&[Move]>,
+) -> String {
+    let result_text = match (result, moves) {
+        (SearchResult::Solved, Some(m)) => format!("Solvable in {} moves", m.len()),
+        (SearchResult::Solved, None) => "Solvable".to_string(),
+        (SearchResult::Unsolvable, _) => "Impossible".to_string(),
+        (SearchResult::Terminated, _) => "Terminated".to_string(),
+        (SearchResult::Crashed, _) => "Crashed".to_string(),
+    };
+    let code = DealCode::new(*deck, draw_step).map_or_else(String::new, |c| c.to_string());
+    let steps = steps(deck, draw_step, moves.unwrap_or_default());
+
+    let since = if resumed { " (since resume)" } else { "" };
+    let mut html = String::new();
+    let rows = [
+        ("Seed", seed.to_string()),
+        ("Deal code", code),
+        ("Draw step", draw_step.to_string()),
+        ("Result", result_text.clone()),
+        (
+            &format!("Time{since}"),
+            format!("{:.2} ms", elapsed.as_secs_f64() * 1000.),
+        ),
+        (
+            &format!("Total visit{since}"),
+            stats.total_visit().to_string(),
+        ),
+        (
+            &format!("Unique visit{since}"),
+            stats.unique_visit().to_string(),
+        ),
+        (&format!("Max depth{since}"), stats.max_depth().to_string()),
+    ];
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} - {}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Klondike {}</h1>\n<table>",
+        escape(&seed.to_string()),
+        escape(&result_text),
+        escape(&seed.to_string()),
+    )
+    .unwrap();
+    for (name, value) in rows {
+        writeln!(html, "<tr><td>{name}</td><td>{}</td></tr>", escape(&value)).unwrap();
+    }
+    // `</` can't appear inside the script
+    let steps = serde_json::to_string(&steps).unwrap().replace("</", "<\\/");
+    writeln!(
+        html,
+        "</table>\n<div id=\"controls\"><button id=\"first\">&#x23EE;</button> <button id=\"back\">&#x25C0;</button> <button id=\"next\">&#x25B6;</button> <button id=\"last\">&#x23ED;</button> <input id=\"slider\" type=\"range\" min=\"0\" value=\"0\"> <span id=\"counter\"></span></div>\n<div id=\"step\"></div>\n<div id=\"board\"></div>\n<script>\nconst STEPS = {steps};\n{SCRIPT}\n</script>\n</body>\n</html>"
+    )
+    .unwrap();
+    html
+}
+
+/// Write the report of `report_html` into `path`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_report(
+    path: &str,
+    seed: &Seed,
+    deck: &CardDeck,
+    draw_step: NonZeroU8,
+    result: SearchResult,
+    stats: &AtomicSearchStats,
+    resumed: bool,
+    elapsed: Duration,
+    moves: Option<&[Move]>,
+) -> io::Result<()> {
+    fs::write(
+        path,
+        report_html(
+            seed, deck, draw_step, result, stats, resumed, elapsed, moves,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use lonelybot::{shuffler::default_shuffle, solver::solve, state::Solitaire};
+
+    use super::*;
+    use crate::SeedType;
+
+    #[test]
+    fn test_report() {
+        let deck = default_shuffle(12);
+        let draw_step = NonZeroU8::new(3).unwrap();
+        let (result, moves) = solve(&mut Solitaire::new(&deck, draw_step));
+        assert_eq!(result, SearchResult::Solved);
+        let moves = moves.unwrap();
+
+        let seed = Seed {
+            seed_type: SeedType::Default,
+            seed: 12.into(),
+        };
+        let html = report_html(
+            &seed,
+            &deck,
+            draw_step,
+            result,
+            &AtomicSearchStats::new(),
+            false,
+            Duration::from_millis(5),
+            Some(&moves),
+        );
+        assert!(html.contains("L-12"));
+        assert!(html.contains(&format!("Solvable in {} moves", moves.len())));
+        assert!(!html.contains("since resume"));
+
+        let steps = steps(&deck, draw_step, &moves);
+        let n_standard = convert_moves(&mut StandardSolitaire::new(&deck, draw_step), &moves)
+            .unwrap()
+            .len();
+        assert_eq!(steps.len(), n_standard + 1);
+        assert_eq!(steps.last().unwrap()["text"], "Won");
+        // the SVG are in the script
+        assert!(!html[html.find("<script>").unwrap() + 8..].contains("</svg>"));
+    }
+}
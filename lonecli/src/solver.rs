@@ -1,23 +1,30 @@
 use core::time::Duration;
 use lonelybot::{
     graph::{graph_with_tracking, Graph},
+    incremental::{IncrementalSolver, Progress},
     solver::{solve_with_tracking, HistoryVec, SearchResult},
+    split::{solve_subproblem_with_tracking, InvalidSubproblem, Subproblem},
     state::Solitaire,
     tracking::TerminateSignal,
     traverse::Control,
 };
 use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
     sync::{
         atomic::{AtomicBool, Ordering},
         mpsc::{channel, RecvTimeoutError},
         Arc,
     },
     thread,
+    time::Instant,
 };
 
 use crate::tracking::AtomicSearchStats;
 
-const STACK_SIZE: usize = 4 * 1024 * 1024;
+pub(crate) const STACK_SIZE: usize = 4 * 1024 * 1024;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(600);
+const STEP_NODES: usize = 1 << 20;
 
 struct TermSignal<'a> {
     term_signal: &'a AtomicBool,
@@ -76,6 +83,102 @@ pub(crate) fn run_solve(
     (res, Arc::try_unwrap(ss).unwrap(), hist)
 }
 
+pub(crate) fn run_solve_part(
+    g: Solitaire,
+    part: Subproblem,
+    term_signal: &Arc<AtomicBool>,
+) -> Result<(SearchResult, Option<HistoryVec>), InvalidSubproblem> {
+    let ss = AtomicSearchStats::new();
+    let term = term_signal.clone();
+
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            solve_subproblem_with_tracking(
+                &g,
+                &part,
+                &ss,
+                &TermSignal {
+                    term_signal: term.as_ref(),
+                },
+            )
+        })
+        .unwrap()
+        .join()
+        .unwrap_or(Ok((SearchResult::Crashed, None)))
+}
+
+fn save_checkpoint(solver: &IncrementalSolver, path: &str) {
+    // the transposition table can be huge, so it's streamed instead of copied into one buffer
+    let write = |path: &str| -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for chunk in solver.save_chunks() {
+            out.write_all(&chunk)?;
+        }
+        out.into_inner()?.sync_all()
+    };
+
+    // write to a temporary file first so a crash while saving doesn't destroy the old checkpoint
+    let tmp = format!("{path}.tmp");
+    if let Err(err) = write(&tmp).and_then(|()| fs::rename(&tmp, path)) {
+        eprintln!("Can't save checkpoint to {path}: {err}");
+    }
+}
+
+/// Same as `run_solve` but the search can be saved to `checkpoint` (periodically and when terminated)
+pub(crate) fn run_solve_checkpoint(
+    mut solver: IncrementalSolver,
+    checkpoint: Option<String>,
+    verbose: bool,
+    term_signal: &Arc<AtomicBool>,
+) -> (SearchResult, AtomicSearchStats, Option<HistoryVec>) {
+    let ss = Arc::new(AtomicSearchStats::new());
+
+    let (send, recv) = channel::<()>();
+
+    let child = {
+        let ss_clone = ss.clone();
+        let term = term_signal.clone();
+        thread::spawn(move || {
+            let sign = TermSignal {
+                term_signal: term.as_ref(),
+            };
+            let mut last_save = Instant::now();
+            while solver.step_with_tracking(STEP_NODES, ss_clone.as_ref(), &sign)
+                == Progress::Pending
+            {
+                if sign.is_terminated() {
+                    if let Some(path) = &checkpoint {
+                        save_checkpoint(&solver, path);
+                    }
+                    break;
+                }
+                if let Some(path) = &checkpoint {
+                    if last_save.elapsed() >= CHECKPOINT_INTERVAL {
+                        save_checkpoint(&solver, path);
+                        last_save = Instant::now();
+                    }
+                }
+            }
+            send.send(()).ok();
+            solver.into_result()
+        })
+    };
+
+    if verbose {
+        loop {
+            match recv.recv_timeout(Duration::from_millis(1000)) {
+                Err(RecvTimeoutError::Disconnected) | Ok(()) => break,
+                Err(RecvTimeoutError::Timeout) => println!("{ss}"),
+            }
+        }
+    }
+
+    let (res, hist) = child.join().unwrap_or((SearchResult::Crashed, None));
+
+    (res, Arc::try_unwrap(ss).unwrap(), hist)
+}
+
 pub(crate) fn run_graph(
     mut g: Solitaire,
     verbose: bool,
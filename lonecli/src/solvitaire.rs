@@ -1,11 +1,13 @@
 use core::fmt;
 
 use lonelybot::{
-    card::{Card, N_SUITS},
+    card::{Card, N_CARDS, N_SUITS},
     deck::N_PILES,
     formatter::NUMBERS,
+    shuffler::{encode_shuffle, CardDeck},
     standard::StandardSolitaire,
 };
+use serde_json::Value;
 
 pub(crate) struct SolvitaireCard<const LOWER: bool>(Card);
 
@@ -95,6 +97,54 @@ impl fmt::Display for Solvitaire {
     }
 }
 
+/// Read the initial deal from a Solitaire document, return `None` if it's not a valid starting position
+pub(crate) fn parse_solvitaire(value: &Value) -> Option<CardDeck> {
+    let cards_of = |value: &Value| -> Option<Vec<Card>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|c| c.as_str()?.parse().ok())
+            .collect()
+    };
+
+    let piles = value.get("tableau piles")?.as_array()?;
+    if piles.len() != N_PILES as usize {
+        return None;
+    }
+
+    let mut cards = Vec::<Card>::with_capacity(N_CARDS as usize);
+    for (i, pile) in piles.iter().enumerate() {
+        let pile = cards_of(pile)?;
+        if pile.len() != i + 1 {
+            return None;
+        }
+        cards.extend(pile);
+    }
+
+    // the stock is listed from the bottom
+    cards.extend(cards_of(value.get("stock")?)?.into_iter().rev());
+
+    // a starting position has nothing in the waste or the foundation
+    let waste_empty = value
+        .get("waste")
+        .is_none_or(|v| v.as_array().is_some_and(Vec::is_empty));
+    let foundation_empty = value.get("foundation").is_none_or(|v| {
+        v.as_array().is_some_and(|stacks| {
+            stacks
+                .iter()
+                .all(|x| x.as_array().is_some_and(Vec::is_empty))
+        })
+    });
+    if !waste_empty || !foundation_empty {
+        return None;
+    }
+
+    let cards: CardDeck = cards.try_into().ok()?;
+    // check that it's a permutation of all the cards
+    encode_shuffle(cards)?;
+    Some(cards)
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU8;
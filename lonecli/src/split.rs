@@ -0,0 +1,270 @@
+use std::{
+    fs,
+    io::{Error, ErrorKind, Result},
+    num::NonZeroU8,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use lonelybot::{
+    moves::Move,
+    shuffler::{encode_shuffle, exact_shuffle, CardDeck, U256},
+    solver::{HistoryVec, SearchResult},
+    split::{merge_results, split, SplitResult, Subproblem},
+    state::Solitaire,
+};
+
+use crate::solver::run_solve_part;
+
+const PART_HEADER: &str = "lonelybot-part 1";
+const RESULT_HEADER: &str = "lonelybot-result 1";
+
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+// the common header of the part and the result files
+struct Header {
+    seed: U256,
+    draw_step: NonZeroU8,
+    index: usize,
+    total: usize,
+}
+
+impl Header {
+    fn write(&self, out: &mut String, header: &str) {
+        out.push_str(&format!(
+            "{header}\ndeal {}\ndraw {}\npart {} {}\n",
+            self.seed, self.draw_step, self.index, self.total
+        ));
+    }
+
+    fn read<'a>(lines: &mut impl Iterator<Item = &'a str>, header: &str) -> Result<Self> {
+        if lines.next() != Some(header) {
+            return Err(invalid("unknown file format"));
+        }
+
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|line| line.strip_prefix(' '))
+                .ok_or_else(|| invalid(&format!("missing {name}")))
+        };
+
+        let seed = U256::from_dec_str(field("deal")?).map_err(|_| invalid("invalid deal"))?;
+        let draw_step = field("draw")?
+            .parse()
+            .map_err(|_| invalid("invalid draw step"))?;
+        let (index, total) = field("part")?
+            .split_once(' ')
+            .and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)))
+            .ok_or_else(|| invalid("invalid part"))?;
+        if index >= total {
+            return Err(invalid("the part index is out of range"));
+        }
+
+        Ok(Self {
+            seed,
+            draw_step,
+            index,
+            total,
+        })
+    }
+
+    fn game(&self) -> Result<Solitaire> {
+        let deck = exact_shuffle(self.seed).ok_or_else(|| invalid("invalid deal"))?;
+        Ok(Solitaire::new(&deck, self.draw_step))
+    }
+}
+
+fn print_solution(history: &HistoryVec) {
+    println!("Solvable in {} moves", history.len());
+    for m in history {
+        print!("{m}, ");
+    }
+    println!();
+}
+
+/// Split the search at `depth` and distribute the subproblems into `n_files` files named `prefix.i`
+pub(crate) fn write_parts(
+    deck: CardDeck,
+    draw_step: NonZeroU8,
+    depth: usize,
+    n_files: usize,
+    prefix: &str,
+) -> Result<()> {
+    let mut game = Solitaire::new(&deck, draw_step);
+    let parts = match split(&mut game, depth) {
+        SplitResult::Solved(history) => {
+            print_solution(&history);
+            return Ok(());
+        }
+        SplitResult::Parts(parts) => parts,
+    };
+
+    let seed = encode_shuffle(deck).ok_or_else(|| invalid("invalid deal"))?;
+    let n_files = n_files.max(1);
+    for index in 0..n_files {
+        let mut out = String::new();
+        Header {
+            seed,
+            draw_step,
+            index,
+            total: n_files,
+        }
+        .write(&mut out, PART_HEADER);
+
+        for part in parts.iter().skip(index).step_by(n_files) {
+            out.push_str(&format!("{part}\n"));
+        }
+        fs::write(format!("{prefix}.{index}"), out)?;
+    }
+    println!("Split into {} subproblems in {n_files} files", parts.len());
+    Ok(())
+}
+
+/// Solve every subproblem in the part file `path` and write the result into `out`
+pub(crate) fn solve_part(path: &str, out: &str, terminated: &Arc<AtomicBool>) -> Result<()> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content.lines();
+    let header = Header::read(&mut lines, PART_HEADER)?;
+    let game = header.game()?;
+
+    let parts = lines
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse::<Subproblem>)
+        .collect::<core::result::Result<Vec<_>, _>>()
+        .map_err(|_| invalid("invalid subproblem"))?;
+
+    let mut results = Vec::new();
+    for part in parts {
+        let res = run_solve_part(game.clone(), part, terminated)
+            .map_err(|_| invalid("the subproblem doesn't belong to the deal"))?;
+        let done = res.0 != SearchResult::Unsolvable;
+        results.push(res);
+        if done {
+            break;
+        }
+    }
+    let (res, history) = merge_results(results);
+
+    let mut content = String::new();
+    header.write(&mut content, RESULT_HEADER);
+    content.push_str(&format!("result {res:?}\n"));
+    if let Some(history) = &history {
+        let codes: Vec<String> = history.iter().map(|m| m.code().to_string()).collect();
+        content.push_str(&format!("moves {}\n", codes.join(" ")));
+        print_solution(history);
+    } else {
+        println!("{res:?}");
+    }
+    fs::write(out, content)
+}
+
+fn read_result(path: &str) -> Result<(Header, (SearchResult, Option<HistoryVec>))> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content.lines();
+    let header = Header::read(&mut lines, RESULT_HEADER)?;
+
+    let res = match lines.next() {
+        Some("result Solved") => SearchResult::Solved,
+        Some("result Unsolvable") => SearchResult::Unsolvable,
+        Some("result Terminated") => SearchResult::Terminated,
+        Some("result Crashed") => SearchResult::Crashed,
+        _ => return Err(invalid("invalid result")),
+    };
+
+    let history = if res == SearchResult::Solved {
+        let codes = lines
+            .next()
+            .and_then(|line| line.strip_prefix("moves"))
+            .ok_or_else(|| invalid("missing moves"))?;
+        let mut history = HistoryVec::new();
+        for code in codes.split_whitespace() {
+            let m = code
+                .parse()
+                .ok()
+                .and_then(Move::from_code)
+                .ok_or_else(|| invalid("invalid move"))?;
+            history.try_push(m).map_err(|_| invalid("too many moves"))?;
+        }
+        Some(history)
+    } else {
+        None
+    };
+    Ok((header, (res, history)))
+}
+
+/// Merge the result files of every part of a split
+pub(crate) fn merge(paths: &[String]) -> Result<()> {
+    let mut results = Vec::new();
+    let mut seen = Vec::<usize>::new();
+    let mut expected: Option<(U256, NonZeroU8, usize)> = None;
+
+    for path in paths {
+        let (header, res) = read_result(path)?;
+        let key = (header.seed, header.draw_step, header.total);
+        if *expected.get_or_insert(key) != key {
+            return Err(invalid("the results come from different splits"));
+        }
+        if seen.contains(&header.index) {
+            return Err(invalid(&format!("part {} is given twice", header.index)));
+        }
+        seen.push(header.index);
+        results.push(res);
+    }
+
+    let (res, history) = merge_results(results);
+    if let Some(history) = &history {
+        print_solution(history);
+        return Ok(());
+    }
+
+    let total = expected.map_or(0, |(_, _, total)| total);
+    if res == SearchResult::Unsolvable && seen.len() < total {
+        // a missing part could still be solvable
+        println!("Incomplete: {} of {total} parts", seen.len());
+    } else if res == SearchResult::Unsolvable {
+        println!("Impossible");
+    } else {
+        println!("{res:?}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_parts() {
+        let write = |name: &str, index: usize, total: usize| {
+            let mut content = String::new();
+            Header {
+                seed: 12.into(),
+                draw_step: NonZeroU8::new(3).unwrap(),
+                index,
+                total,
+            }
+            .write(&mut content, RESULT_HEADER);
+            content.push_str("result Unsolvable\n");
+            let path = env::temp_dir().join(format!("lonecli-merge-{}-{name}", std::process::id()));
+            fs::write(&path, content).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+
+        let first = write("0", 0, 2);
+        let second = write("1", 1, 2);
+        let out_of_range = write("2", 2, 2);
+
+        assert!(merge(&[first.clone(), second.clone()]).is_ok());
+        assert!(merge(&[first.clone(), first.clone()]).is_err());
+        assert!(merge(&[first.clone(), out_of_range.clone()]).is_err());
+
+        for path in [first, second, out_of_range] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
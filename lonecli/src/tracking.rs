@@ -1,6 +1,7 @@
 use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 
 use lonelybot::tracking::SearchStatistics;
+use serde_json::{json, Value};
 
 const TRACK_DEPTH: usize = 8;
 
@@ -32,6 +33,15 @@ impl AtomicSearchStats {
     pub(crate) fn max_depth(&self) -> usize {
         self.max_depth.load(Ordering::Relaxed)
     }
+
+    #[must_use]
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "total_visit": self.total_visit(),
+            "unique_visit": self.unique_visit(),
+            "max_depth": self.max_depth(),
+        })
+    }
 }
 
 impl SearchStatistics for AtomicSearchStats {
@@ -1,118 +1,781 @@
-use core::fmt;
-
-use lonelybot::card::{Card, N_SUITS};
-use lonelybot::deck::{Drawable, N_PILES};
-use lonelybot::formatter::{NUMBERS, SYMBOLS};
-use lonelybot::stack::Stack;
-use lonelybot::standard::{HiddenVec, PileVec, StandardSolitaire};
-use lonelybot::state::Solitaire;
-
-use colored::{Color, Colorize};
-
-pub(crate) const COLOR: [Color; N_SUITS as usize] =
-    [Color::Red, Color::Red, Color::Black, Color::Black];
-
-pub(crate) struct ColoredCard(Option<Card>);
-
-impl fmt::Display for ColoredCard {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let Some(c) = self.0 {
-            let (u, v) = c.split();
-            write!(
-                f,
-                "{}{}",
-                NUMBERS[u as usize].black().on_white(),
-                SYMBOLS[v as usize].on_white().color(COLOR[v as usize])
-            )
-        } else {
-            write!(f, "  ")
-        }
+use std::{
+    io::{self, Write},
+    num::NonZeroU8,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+    },
+    terminal::{self, ClearType},
+};
+use lonelybot::{
+    card::{Card, N_SUITS},
+    convert::convert_moves,
+    deck::N_PILES,
+    formatter::{NUMBERS, SYMBOLS},
+    moves::Move,
+    shuffler::CardDeck,
+    solver::{solve_with_tracking, SearchResult},
+    standard::{Pos, StandardMove, StandardSolitaire},
+    state::Solitaire,
+    tracking::{StateLimit, TerminateSignal},
+};
+
+use crate::{shuffle, solver::STACK_SIZE, Seed};
+
+// the solver gives up on a position after this many unique states
+const MAX_STATES: usize = 3_000_000;
+const COLUMN_WIDTH: u16 = 6;
+const TOP_ROW: u16 = 2;
+const TABLEAU_ROW: u16 = 5;
+
+/// What the solver knows about the current position
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Solvability {
+    Checking,
+    /// With a winning line from the current position
+    Solvable(Vec<Move>),
+    Unsolvable,
+    /// The search ran out of states
+    Unknown,
+}
+
+/// A place on the board the cursor can be on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Spot {
+    Stock,
+    Waste,
+    Foundation(u8),
+    /// A pile and the index of a face up card in it
+    Pile(u8, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// Pick the card under the cursor, or drop the picked card there
+    Select,
+    Cancel,
+    Draw,
+    Undo,
+    Redo,
+    Hint,
+    Quit,
+}
+
+/// Play `m`, and count the waste cards shown in `fan`
+fn apply(game: &mut StandardSolitaire, fan: &mut usize, m: &StandardMove) -> bool {
+    let before = game.get_deck().waste_iter().len();
+    if game.do_move(m).is_err() {
+        return false;
     }
+    if *m == StandardMove::DRAW_NEXT {
+        // nothing is turned over when the waste goes back to the stock
+        *fan = game.get_deck().waste_iter().len().saturating_sub(before);
+    } else if m.from == Pos::Deck {
+        *fan = fan.saturating_sub(1);
+    }
+    true
 }
 
-fn color(c: Option<Card>) -> ColoredCard {
-    ColoredCard(c)
+struct App {
+    deck: CardDeck,
+    draw_step: NonZeroU8,
+    game: StandardSolitaire,
+    /// The waste cards turned over by the last draw which are still on the waste
+    fan: usize,
+    history: Vec<StandardMove>,
+    redo: Vec<StandardMove>,
+    cursor: Spot,
+    picked: Option<(Pos, Card)>,
+    hint: Option<StandardMove>,
+    status: Solvability,
+    /// Increased on every change of the position, to drop the outdated solver results
+    generation: u64,
+    message: String,
+    quit: bool,
 }
 
-pub(crate) fn print_foundation(stack: &Stack) {
-    print!("\t\t");
-    // print out the foundation stack
-    for i in 0..N_SUITS {
-        let card = stack.get(i);
-        let card = if card == 0 {
-            None
-        } else {
-            Some(Card::new(card - 1, i))
+impl App {
+    fn new(deck: CardDeck, draw_step: NonZeroU8) -> Self {
+        Self {
+            deck,
+            draw_step,
+            game: StandardSolitaire::new(&deck, draw_step),
+            fan: 0,
+            history: Vec::new(),
+            redo: Vec::new(),
+            cursor: Spot::Pile(0, 0),
+            picked: None,
+            hint: None,
+            status: Solvability::Checking,
+            generation: 0,
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    // a past position is rebuilt from the start
+    fn replay(&self, moves: &[StandardMove]) -> (StandardSolitaire, usize) {
+        let mut game = StandardSolitaire::new(&self.deck, self.draw_step);
+        let mut fan = 0;
+        for m in moves {
+            assert!(apply(&mut game, &mut fan, m));
+        }
+        (game, fan)
+    }
+
+    fn changed(&mut self) {
+        self.generation += 1;
+        self.status = Solvability::Checking;
+        self.picked = None;
+        self.hint = None;
+        self.clamp_cursor();
+        if self.game.is_win() {
+            self.message = format!("You won in {} moves!", self.history.len());
+        }
+    }
+
+    fn play(&mut self, m: StandardMove) -> bool {
+        if !apply(&mut self.game, &mut self.fan, &m) {
+            self.message = format!("Illegal move {m}");
+            return false;
+        }
+        self.history.push(m);
+        self.redo.clear();
+        self.message.clear();
+        self.changed();
+        true
+    }
+
+    fn undo(&mut self) {
+        let Some(m) = self.history.pop() else {
+            self.message = "Nothing to undo".to_string();
+            return;
         };
-        print!("{}.{} ", i + 1, color(card));
-    }
-    println!();
-}
-
-pub(crate) fn print_piles(
-    piles: &[PileVec; N_PILES as usize],
-    hidden: &[HiddenVec; N_PILES as usize],
-) {
-    for i in 0..N_PILES {
-        print!("{}\t", i + 5);
-    }
-    println!();
-
-    // printing
-    for i in 0.. {
-        let mut is_print = false;
-        for j in 0..N_PILES as usize {
-            let cur_pile = &piles[j];
-
-            let n_visible = cur_pile.len();
-            let n_hidden = hidden[j].len();
-            if n_hidden > i {
-                print!("**\t");
-                is_print = true;
-            } else if i < n_hidden + n_visible {
-                print!("{}\t", color(Some(cur_pile[i - n_hidden])));
-                is_print = true;
-            } else {
-                print!("  \t");
+        (self.game, self.fan) = self.replay(&self.history);
+        self.redo.push(m);
+        self.message = format!("Undo {m}");
+        self.changed();
+    }
+
+    fn redo(&mut self) {
+        let Some(m) = self.redo.pop() else {
+            self.message = "Nothing to redo".to_string();
+            return;
+        };
+        assert!(apply(&mut self.game, &mut self.fan, &m));
+        self.history.push(m);
+        self.message = format!("Redo {m}");
+        self.changed();
+    }
+
+    fn hint(&mut self) {
+        self.message = match &self.status {
+            _ if self.game.is_win() => "Already won".to_string(),
+            Solvability::Checking => "Still checking, try again in a moment".to_string(),
+            Solvability::Unsolvable => "No winning line from here, undo some moves".to_string(),
+            Solvability::Unknown => "Too hard for the solver".to_string(),
+            Solvability::Solvable(solution) => {
+                // the first solver move can take a few standard moves (e.g. drawing first)
+                match convert_moves(&mut self.game.clone(), &solution[..1]) {
+                    Ok(moves) => {
+                        self.hint = moves.first().copied();
+                        format!("Hint: {}", moves[0])
+                    }
+                    Err(_) => "No hint available".to_string(),
+                }
             }
+        };
+    }
+
+    fn on_solved(&mut self, generation: u64, status: Solvability) {
+        if generation == self.generation {
+            self.status = status;
         }
-        println!();
-        if !is_print {
-            break;
+    }
+
+    fn pile_len(&self, pile: u8) -> usize {
+        self.game.get_piles()[usize::from(pile)].len()
+    }
+
+    fn clamp_cursor(&mut self) {
+        if let Spot::Pile(pile, index) = self.cursor {
+            self.cursor = Spot::Pile(pile, index.min(self.pile_len(pile).saturating_sub(1)));
+        }
+    }
+
+    // the column of the spot, the top row has the stock, the waste (over two columns) and the foundation
+    fn column(spot: Spot) -> u8 {
+        match spot {
+            Spot::Stock => 0,
+            Spot::Waste => 1,
+            Spot::Foundation(suit) => N_PILES - N_SUITS + suit,
+            Spot::Pile(pile, _) => pile,
+        }
+    }
+
+    fn top_spot(column: u8) -> Spot {
+        match column {
+            0 => Spot::Stock,
+            c if c < N_PILES - N_SUITS => Spot::Waste,
+            c => Spot::Foundation(c - (N_PILES - N_SUITS)),
+        }
+    }
+
+    fn pile_spot(&self, pile: u8) -> Spot {
+        Spot::Pile(pile, self.pile_len(pile).saturating_sub(1))
+    }
+
+    fn move_cursor(&mut self, action: Action) {
+        let column = Self::column(self.cursor);
+        self.cursor = match (self.cursor, action) {
+            (Spot::Pile(pile, _), Action::Left) => self.pile_spot((pile + N_PILES - 1) % N_PILES),
+            (Spot::Pile(pile, _), Action::Right) => self.pile_spot((pile + 1) % N_PILES),
+            (Spot::Pile(pile, index), Action::Up) => {
+                if index > 0 {
+                    Spot::Pile(pile, index - 1)
+                } else {
+                    Self::top_spot(pile)
+                }
+            }
+            (Spot::Pile(pile, index), Action::Down) => {
+                Spot::Pile(pile, (index + 1).min(self.pile_len(pile).saturating_sub(1)))
+            }
+            (_, Action::Left) => Self::top_spot(match Self::top_spot(column) {
+                Spot::Stock => N_PILES - 1,
+                Spot::Foundation(0) => 1,
+                _ => column - 1,
+            }),
+            (_, Action::Right) => Self::top_spot(match Self::top_spot(column) {
+                Spot::Waste => N_PILES - N_SUITS,
+                Spot::Foundation(suit) if suit == N_SUITS - 1 => 0,
+                _ => column + 1,
+            }),
+            (_, Action::Down) => self.pile_spot(column),
+            (spot, _) => spot,
+        };
+    }
+
+    // the card which can be picked at the spot
+    fn card_at(&self, spot: Spot) -> Option<(Pos, Card)> {
+        match spot {
+            Spot::Stock => None,
+            Spot::Waste => self.game.get_deck().peek_current().map(|c| (Pos::Deck, c)),
+            Spot::Foundation(suit) => match self.game.get_stack().get(suit) {
+                0 => None,
+                n => Some((Pos::Stack(suit), Card::new(n - 1, suit))),
+            },
+            Spot::Pile(pile, index) => self.game.get_piles()[usize::from(pile)]
+                .get(index)
+                .map(|&c| (Pos::Pile(pile), c)),
         }
     }
+
+    fn select(&mut self) {
+        if self.cursor == Spot::Stock {
+            self.play(StandardMove::DRAW_NEXT);
+            return;
+        }
+        match self.picked.take() {
+            None => match self.card_at(self.cursor) {
+                Some(picked) => {
+                    self.picked = Some(picked);
+                    self.message = format!("Picked {}, choose where to put it", picked.1);
+                }
+                None => self.message = "Nothing to pick here".to_string(),
+            },
+            Some((from, card)) => {
+                let to = match self.cursor {
+                    Spot::Foundation(suit) => Pos::Stack(suit),
+                    Spot::Pile(pile, _) => Pos::Pile(pile),
+                    Spot::Stock | Spot::Waste => Pos::Deck,
+                };
+                if to == from {
+                    self.message.clear();
+                } else {
+                    self.play(StandardMove::new(from, to, card));
+                }
+            }
+        }
+    }
+
+    fn act(&mut self, action: Action) {
+        match action {
+            Action::Left | Action::Right | Action::Up | Action::Down => self.move_cursor(action),
+            Action::Select => self.select(),
+            Action::Cancel => {
+                self.picked = None;
+                self.hint = None;
+                self.message.clear();
+            }
+            Action::Draw => {
+                self.play(StandardMove::DRAW_NEXT);
+            }
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Hint => self.hint(),
+            Action::Quit => self.quit = true,
+        }
+    }
+}
+
+fn action(key: KeyEvent) -> Option<Action> {
+    Some(match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+        KeyCode::Left => Action::Left,
+        KeyCode::Right => Action::Right,
+        KeyCode::Up => Action::Up,
+        KeyCode::Down => Action::Down,
+        KeyCode::Enter | KeyCode::Char(' ') => Action::Select,
+        KeyCode::Esc => Action::Cancel,
+        KeyCode::Char('d') => Action::Draw,
+        KeyCode::Char('u') | KeyCode::Backspace => Action::Undo,
+        KeyCode::Char('r') => Action::Redo,
+        KeyCode::Char('h' | '?') => Action::Hint,
+        KeyCode::Char('q') => Action::Quit,
+        _ => return None,
+    })
+}
+
+struct Stale<'a> {
+    generation: u64,
+    current: &'a AtomicU64,
 }
 
-pub(crate) fn print_game(game: &Solitaire) {
-    // print out the deck
-    for (pos, card, t) in game.get_deck().iter_all() {
-        let s = format!("{pos} ");
-        let prefix = match t {
-            Drawable::None => format!(" {}", s.bright_black()),
-            Drawable::Current => format!(">{}", s.on_blue()),
-            Drawable::Next => format!("+{}", s.on_bright_blue()),
+impl TerminateSignal for Stale<'_> {
+    fn is_terminated(&self) -> bool {
+        self.current.load(Ordering::Relaxed) != self.generation
+    }
+}
+
+type Job = (u64, Solitaire);
+type Solved = (u64, Solvability);
+
+/// Solve the positions sent to the thread, the search is dropped once the position changes
+fn spawn_solver(current: Arc<AtomicU64>) -> io::Result<(Sender<Job>, Receiver<Solved>)> {
+    let (job_tx, job_rx) = mpsc::channel::<Job>();
+    let (res_tx, res_rx) = mpsc::channel();
+
+    // the search is recursive, like the other solver threads
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            while let Ok(mut job) = job_rx.recv() {
+                // only the last position matters
+                while let Ok(newer) = job_rx.try_recv() {
+                    job = newer;
+                }
+                let (generation, mut game) = job;
+                let sign = Stale {
+                    generation,
+                    current: &current,
+                };
+                let limit = StateLimit::new(MAX_STATES, &sign);
+                let status = match solve_with_tracking(&mut game, &limit, &limit) {
+                    (SearchResult::Solved, Some(moves)) => Solvability::Solvable(moves.to_vec()),
+                    (SearchResult::Unsolvable, _) => Solvability::Unsolvable,
+                    _ => Solvability::Unknown,
+                };
+                if res_tx.send((generation, status)).is_err() {
+                    break;
+                }
+            }
+        })?;
+    Ok((job_tx, res_rx))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    None,
+    Cursor,
+    Picked,
+    Hint,
+}
+
+fn print_card(out: &mut impl Write, card: Option<Card>, mark: Mark) -> io::Result<()> {
+    let background = match mark {
+        Mark::Picked => Color::Yellow,
+        Mark::Hint => Color::Cyan,
+        Mark::None | Mark::Cursor => Color::White,
+    };
+    if mark == Mark::Cursor {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+    }
+    match card {
+        Some(c) => {
+            let (rank, suit) = c.split();
+            let color = if suit < 2 {
+                Color::DarkRed
+            } else {
+                Color::Black
+            };
+            queue!(
+                out,
+                SetBackgroundColor(background),
+                SetForegroundColor(color),
+                Print(format!(
+                    "{:>2}{}",
+                    NUMBERS[usize::from(rank)],
+                    SYMBOLS[usize::from(suit)]
+                )),
+            )?;
+        }
+        None if mark == Mark::None => queue!(out, Print("[ ]"))?,
+        None => queue!(out, SetBackgroundColor(background), Print("[ ]"))?,
+    }
+    queue!(out, SetAttribute(Attribute::Reset), ResetColor)
+}
+
+fn print_back(out: &mut impl Write, mark: Mark) -> io::Result<()> {
+    if mark == Mark::Cursor {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+    }
+    let background = if mark == Mark::Hint {
+        Color::Cyan
+    } else {
+        Color::DarkBlue
+    };
+    queue!(
+        out,
+        SetBackgroundColor(background),
+        SetForegroundColor(Color::White),
+        Print("###"),
+        SetAttribute(Attribute::Reset),
+        ResetColor
+    )
+}
+
+fn column_x(column: u8) -> u16 {
+    2 + u16::from(column) * COLUMN_WIDTH
+}
+
+fn draw(out: &mut impl Write, app: &App, title: &str) -> io::Result<()> {
+    let game = &app.game;
+    // `is_top` for the spots a card can be put on
+    let mark = |spot: Spot, pos: Pos, card: Option<Card>, is_top: bool| {
+        let hinted = app.hint.is_some_and(|h| {
+            if h == StandardMove::DRAW_NEXT {
+                spot == Spot::Stock
+            } else {
+                (h.from == pos && card == Some(h.card)) || (h.to == pos && is_top)
+            }
+        });
+        if app.cursor == spot {
+            Mark::Cursor
+        } else if app.picked.is_some_and(|(p, c)| p == pos && Some(c) == card) {
+            Mark::Picked
+        } else if hinted {
+            Mark::Hint
+        } else {
+            Mark::None
+        }
+    };
+
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    let status = match (&app.status, game.is_win()) {
+        (_, true) => "Won",
+        (Solvability::Checking, _) => "Checking...",
+        (Solvability::Solvable(_), _) => "Solvable",
+        (Solvability::Unsolvable, _) => "Unsolvable",
+        (Solvability::Unknown, _) => "Unknown",
+    };
+    let status_color = match (&app.status, game.is_win()) {
+        (Solvability::Solvable(_), _) | (_, true) => Color::Green,
+        (Solvability::Unsolvable, _) => Color::Red,
+        _ => Color::Yellow,
+    };
+    queue!(
+        out,
+        Print(format!("{title}   Moves: {}   Solver: ", app.history.len())),
+        SetForegroundColor(status_color),
+        Print(status),
+        ResetColor,
+    )?;
+
+    // stock
+    let deck = game.get_deck();
+    let n_stock = deck.deck_iter().len();
+    queue!(out, cursor::MoveTo(column_x(0), TOP_ROW))?;
+    let stock_mark = mark(Spot::Stock, Pos::Deck, None, false);
+    if n_stock > 0 {
+        print_back(out, stock_mark)?;
+    } else {
+        print_card(out, None, stock_mark)?;
+    }
+    queue!(
+        out,
+        cursor::MoveTo(column_x(0), TOP_ROW + 1),
+        Print(format!("{n_stock:>3}"))
+    )?;
+
+    // the cards of the last draw fanned over two columns, only the top one can be picked
+    let waste: Vec<Card> = deck.waste_iter().collect();
+    let n_shown = app.fan.max(1);
+    let shown = &waste[waste.len().saturating_sub(n_shown)..];
+    queue!(out, cursor::MoveTo(column_x(1), TOP_ROW))?;
+    if shown.is_empty() {
+        print_card(out, None, mark(Spot::Waste, Pos::Deck, None, false))?;
+    }
+    for (i, &c) in shown.iter().enumerate() {
+        let m = if i + 1 == shown.len() {
+            mark(Spot::Waste, Pos::Deck, Some(c), false)
+        } else {
+            Mark::None
+        };
+        print_card(out, Some(c), m)?;
+        queue!(out, Print(" "))?;
+    }
+
+    // foundation
+    for suit in 0..N_SUITS {
+        let card = match game.get_stack().get(suit) {
+            0 => None,
+            n => Some(Card::new(n - 1, suit)),
         };
-        print!("{}{} ", prefix, color(Some(card)));
+        queue!(
+            out,
+            cursor::MoveTo(column_x(N_PILES - N_SUITS + suit), TOP_ROW)
+        )?;
+        print_card(
+            out,
+            card,
+            mark(Spot::Foundation(suit), Pos::Stack(suit), card, true),
+        )?;
+        if card.is_none() {
+            queue!(
+                out,
+                cursor::MoveTo(column_x(N_PILES - N_SUITS + suit) + 1, TOP_ROW),
+                Print(SYMBOLS[usize::from(suit)])
+            )?;
+        }
     }
-    println!();
 
-    print_foundation(game.get_stack());
+    // tableau
+    let mut bottom = TABLEAU_ROW;
+    for pile in 0..N_PILES {
+        let x = column_x(pile);
+        queue!(
+            out,
+            cursor::MoveTo(x, TABLEAU_ROW - 1),
+            Print(format!(" {}", pile + 1))
+        )?;
+        let hidden = game.get_hidden()[usize::from(pile)].len();
+        let cards = &game.get_piles()[usize::from(pile)];
+        let mut y = TABLEAU_ROW;
+        for _ in 0..hidden {
+            queue!(out, cursor::MoveTo(x, y))?;
+            print_back(out, Mark::None)?;
+            y += 1;
+        }
+        if cards.is_empty() {
+            queue!(out, cursor::MoveTo(x, y))?;
+            print_card(
+                out,
+                None,
+                mark(Spot::Pile(pile, 0), Pos::Pile(pile), None, true),
+            )?;
+            y += 1;
+        }
+        for (i, &c) in cards.iter().enumerate() {
+            queue!(out, cursor::MoveTo(x, y))?;
+            let m = mark(
+                Spot::Pile(pile, i),
+                Pos::Pile(pile),
+                Some(c),
+                i + 1 == cards.len(),
+            );
+            print_card(out, Some(c), m)?;
+            y += 1;
+        }
+        bottom = bottom.max(y);
+    }
+
+    queue!(
+        out,
+        cursor::MoveTo(0, bottom + 1),
+        Print(&app.message),
+        cursor::MoveTo(0, bottom + 3),
+        Print("arrows move  enter/space pick and drop  esc cancel  d draw  u undo  r redo  h hint  q quit"),
+    )?;
+    out.flush()
+}
+
+// restore the terminal even when the game stops on an error
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+/// Play the game in a full screen terminal UI, with the solver checking every position
+pub(crate) fn play(seed: &Seed, draw_step: NonZeroU8) -> io::Result<()> {
+    let mut app = App::new(shuffle(seed), draw_step);
+    let title = format!("Klondike {seed} draw {draw_step}");
+
+    let current = Arc::new(AtomicU64::new(app.generation));
+    let (jobs, results) = spawn_solver(Arc::clone(&current))?;
+    let mut sent = None;
+
+    let _raw = RawMode::enable()?;
+    let mut out = io::stdout();
+    let mut dirty = true;
+    while !app.quit {
+        if sent != Some(app.generation) {
+            current.store(app.generation, Ordering::Relaxed);
+            jobs.send((app.generation, Solitaire::from(&app.game))).ok();
+            sent = Some(app.generation);
+        }
+        while let Ok((generation, status)) = results.try_recv() {
+            app.on_solved(generation, status);
+            dirty = true;
+        }
+        if dirty {
+            draw(&mut out, &app, &title)?;
+            dirty = false;
+        }
 
-    let piles: [PileVec; N_PILES as usize] = game.compute_visible_piles();
-    let hidden: [HiddenVec; N_PILES as usize] = game.get_hidden().to_piles();
-    print_piles(&piles, &hidden);
+        if event::poll(Duration::from_millis(100))? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    if let Some(action) = action(key) {
+                        app.act(action);
+                    }
+                    dirty = true;
+                }
+                Event::Resize(..) => dirty = true,
+                _ => {}
+            }
+        }
+    }
+    // stop the running search
+    current.store(u64::MAX, Ordering::Relaxed);
+    Ok(())
 }
 
-pub(crate) fn _print_standard_game(game: &StandardSolitaire) {
-    // print out the deck
-    print!("0. ");
-    for card in game.get_deck().peek_waste::<3>() {
-        print!("{} ", color(Some(card)));
+#[cfg(test)]
+mod tests {
+    use lonelybot::shuffler::default_shuffle;
+
+    use super::*;
+
+    #[test]
+    fn test_play() {
+        let mut app = App::new(default_shuffle(12), NonZeroU8::new(3).unwrap());
+
+        // 3♥ of the 4th pile onto the 4♣ of the 5th one
+        app.cursor = Spot::Pile(3, 0);
+        app.act(Action::Select);
+        assert_eq!(app.picked.map(|p| p.1.to_string()), Some("3♥".to_string()));
+        app.act(Action::Right);
+        assert_eq!(app.cursor, Spot::Pile(4, 0));
+        app.act(Action::Select);
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.game.get_piles()[4].len(), 2);
+        assert_eq!(app.status, Solvability::Checking);
+
+        // an illegal move is refused
+        app.cursor = Spot::Pile(0, 0);
+        app.act(Action::Select);
+        app.act(Action::Left);
+        app.act(Action::Select);
+        assert_eq!(app.history.len(), 1);
+        assert!(app.message.starts_with("Illegal"));
+
+        app.act(Action::Up);
+        assert_eq!(app.cursor, Spot::Foundation(3));
+        app.act(Action::Right);
+        assert_eq!(app.cursor, Spot::Stock);
+        app.act(Action::Select);
+        app.act(Action::Draw);
+        assert_eq!(app.history.len(), 3);
+        assert_eq!(app.game.get_deck().waste_iter().len(), 6);
+
+        assert_eq!(app.fan, 3);
+        app.act(Action::Undo);
+        app.act(Action::Undo);
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.game.get_deck().waste_iter().len(), 0);
+        app.act(Action::Redo);
+        assert_eq!(app.history.len(), 2);
+        assert_eq!(app.game.get_deck().waste_iter().len(), 3);
+
+        // the hint is the first move of the solution
+        let generation = app.generation;
+        let (result, solution) = solve_with_tracking(
+            &mut Solitaire::from(&app.game),
+            &lonelybot::tracking::EmptySearchStats {},
+            &lonelybot::tracking::DefaultTerminateSignal {},
+        );
+        assert_eq!(result, SearchResult::Solved);
+        app.on_solved(generation - 1, Solvability::Unsolvable);
+        assert_eq!(app.status, Solvability::Checking);
+        app.on_solved(
+            generation,
+            Solvability::Solvable(solution.unwrap().to_vec()),
+        );
+        app.act(Action::Hint);
+        let hint = app.hint.unwrap();
+        assert!(app.game.validate_move(&hint));
+        assert!(app.play(hint));
     }
 
-    print_foundation(game.get_stack());
+    #[test]
+    fn test_fan() {
+        let mut game = StandardSolitaire::new(&default_shuffle(12), NonZeroU8::new(5).unwrap());
+        let mut fan = 0;
+        // 24 cards in the stock, the last draw only turns over 4 of them
+        for expected in [5, 5, 5, 5, 4, 0] {
+            assert!(apply(&mut game, &mut fan, &StandardMove::DRAW_NEXT));
+            assert_eq!(fan, expected);
+        }
 
-    let piles = game.get_piles();
-    let hidden = game.get_hidden();
-    print_piles(piles, hidden);
+        // playing off the waste uncovers a card of the same draw
+        let targets: Vec<Pos> = (0..N_PILES)
+            .map(Pos::Pile)
+            .chain((0..N_SUITS).map(Pos::Stack))
+            .collect();
+        for _ in 0..20 {
+            assert!(apply(&mut game, &mut fan, &StandardMove::DRAW_NEXT));
+            let Some(card) = game.get_deck().peek_current() else {
+                continue;
+            };
+            let before = fan;
+            if let Some(m) = targets
+                .iter()
+                .map(|&to| StandardMove::new(Pos::Deck, to, card))
+                .find(|m| game.validate_move(m))
+            {
+                assert!(apply(&mut game, &mut fan, &m));
+                assert_eq!(fan, before - 1);
+                return;
+            }
+        }
+        panic!("no card could be played off the waste");
+    }
 }
@@ -0,0 +1,241 @@
+use std::{
+    fs,
+    io::{self, Read},
+    num::NonZeroU8,
+};
+
+use clap::ValueEnum;
+use lonelybot::{
+    moves::Move,
+    notation::{parse_klondike_solver, parse_minimal_klondike, NotationError},
+    standard::{StandardHistoryVec, StandardMove},
+    state::Solitaire,
+    verify::{verify_moves, verify_standard, VerifyError},
+};
+use serde_json::json;
+
+use crate::{shuffle, OutputFormat, Seed};
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Notation {
+    /// The moves of the solver, e.g. `DP 5♠, R 3♥`
+    Moves,
+    /// The standard moves, e.g. `5♠:3▸♠ =`
+    Standard,
+    /// The MinimalKlondike letters, e.g. `@@AF GB`
+    Minimal,
+    /// The moves of Klondike-Solver, e.g. `DR2 W5 73-2 F7`
+    KlondikeSolver,
+}
+
+fn split_minimal(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().filter(|c| !c.is_whitespace() && *c != ',');
+    while let Some(c) = chars.next() {
+        if c == '@' {
+            tokens.push(c.to_string());
+        } else {
+            tokens.push([Some(c), chars.next()].into_iter().flatten().collect());
+        }
+    }
+    tokens
+}
+
+fn detect_notation(s: &str) -> Notation {
+    let mut words = s.split_whitespace().peekable();
+    if s.contains('▸') || s.contains(':') || (words.peek().is_some() && words.all(|x| x == "=")) {
+        Notation::Standard
+    } else if s
+        .split(',')
+        .map(str::trim)
+        .find(|x| !x.is_empty())
+        .is_some_and(|x| x.parse::<Move>().is_ok())
+    {
+        Notation::Moves
+    } else if s.chars().any(|c| c.is_ascii_digit()) {
+        // the MinimalKlondike letters have no digit
+        Notation::KlondikeSolver
+    } else {
+        Notation::Minimal
+    }
+}
+
+fn tokenize(s: &str, notation: Notation) -> Vec<String> {
+    match notation {
+        Notation::Moves => s
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Notation::Standard | Notation::KlondikeSolver => s
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|x| !x.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Notation::Minimal => split_minimal(s),
+    }
+}
+
+enum Failure {
+    Parse(usize),
+    Verify(VerifyError),
+}
+
+fn check(game: &Solitaire, tokens: &[String], notation: Notation) -> Result<(), Failure> {
+    fn parse_all<T>(tokens: &[String], f: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, Failure> {
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(i, x)| f(x).ok_or(Failure::Parse(i)))
+            .collect()
+    }
+
+    // the notations without the cards are converted while replaying them
+    let converted = |res: Result<StandardHistoryVec, NotationError>| {
+        res.map_err(|err| match err {
+            NotationError::InvalidToken(i) => Failure::Parse(i),
+            NotationError::IllegalMove(i) => Failure::Verify(VerifyError::IllegalMove(i)),
+        })
+    };
+
+    match notation {
+        Notation::Moves => verify_moves(game, &parse_all(tokens, |x| x.parse::<Move>().ok())?),
+        Notation::Standard => verify_standard(
+            game,
+            &parse_all(tokens, |x| x.parse::<StandardMove>().ok())?,
+        ),
+        Notation::Minimal => verify_standard(
+            game,
+            &converted(parse_minimal_klondike(game, &tokens.concat()))?,
+        ),
+        Notation::KlondikeSolver => verify_standard(
+            game,
+            &converted(parse_klondike_solver(game, &tokens.join(" ")))?,
+        ),
+    }
+    .map_err(Failure::Verify)
+}
+
+/// Replay the moves in the file at `path` (`-` for stdin) and report whether they win the game
+pub(crate) fn verify(
+    seed: &Seed,
+    draw_step: NonZeroU8,
+    path: &str,
+    notation: Option<Notation>,
+    format: OutputFormat,
+) -> io::Result<()> {
+    let input = if path == "-" {
+        let mut s = String::new();
+        io::stdin().read_to_string(&mut s)?;
+        s
+    } else {
+        fs::read_to_string(path)?
+    };
+
+    let notation = notation.unwrap_or_else(|| detect_notation(&input));
+    let tokens = tokenize(&input, notation);
+    let game = Solitaire::new(&shuffle(seed), draw_step);
+    let res = check(&game, &tokens, notation);
+
+    let (error, index) = match res {
+        Ok(()) => (None, None),
+        Err(Failure::Parse(i)) => (Some("invalid_notation"), Some(i)),
+        Err(Failure::Verify(VerifyError::IllegalMove(i))) => (Some("illegal_move"), Some(i)),
+        Err(Failure::Verify(VerifyError::NotWon)) => (Some("not_won"), None),
+    };
+
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            json!({
+                "seed": seed.to_string(),
+                "draw_step": draw_step.get(),
+                "notation": format!("{notation:?}"),
+                "n_moves": tokens.len(),
+                "valid": error.is_none(),
+                "error": error,
+                "index": index,
+                "move": index.map(|i| &tokens[i]),
+            })
+        );
+        return Ok(());
+    }
+
+    match res {
+        Ok(()) => println!("Valid: won in {} moves", tokens.len()),
+        Err(Failure::Parse(i)) => println!("Invalid: can't read move {} `{}`", i + 1, tokens[i]),
+        Err(Failure::Verify(VerifyError::IllegalMove(i))) => {
+            println!("Invalid: move {} `{}` is illegal", i + 1, tokens[i]);
+        }
+        Err(Failure::Verify(VerifyError::NotWon)) => {
+            println!("Invalid: the game is not won after {} moves", tokens.len());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lonelybot::{
+        convert::convert_moves, shuffler::default_shuffle, solver::solve,
+        standard::StandardSolitaire,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_notations() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let moves = solve(&mut game.clone()).1.unwrap();
+        let standard = convert_moves(&mut StandardSolitaire::from(&game), &moves).unwrap();
+
+        let text = moves
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        assert_eq!(detect_notation(&text), Notation::Moves);
+        assert!(check(&game, &tokenize(&text, Notation::Moves), Notation::Moves).is_ok());
+
+        let text = standard
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("  ");
+        assert_eq!(detect_notation(&text), Notation::Standard);
+        assert!(check(
+            &game,
+            &tokenize(&text, Notation::Standard),
+            Notation::Standard
+        )
+        .is_ok());
+
+        let text = lonelybot::notation::to_minimal_klondike(&standard);
+        assert_eq!(detect_notation(&text), Notation::Minimal);
+        assert!(check(
+            &game,
+            &tokenize(&text, Notation::Minimal),
+            Notation::Minimal
+        )
+        .is_ok());
+
+        let text = lonelybot::notation::to_klondike_solver(&game, &standard).unwrap();
+        assert_eq!(detect_notation(&text), Notation::KlondikeSolver);
+        assert!(check(
+            &game,
+            &tokenize(&text, Notation::KlondikeSolver),
+            Notation::KlondikeSolver
+        )
+        .is_ok());
+
+        assert!(matches!(
+            check(&game, &tokenize("AB", Notation::Minimal), Notation::Minimal),
+            Err(Failure::Verify(VerifyError::IllegalMove(0)))
+        ));
+        assert!(matches!(
+            check(&game, &tokenize("XY", Notation::Minimal), Notation::Minimal),
+            Err(Failure::Parse(0))
+        ));
+    }
+}
@@ -0,0 +1,40 @@
+# Print the Klondike deals of PySolFC above 32000 with the Mersenne Twister of CPython,
+# used for the expected deals of `pysol_shuffle` in src/shuffler.rs (checked with CPython 3.11.7)
+#
+# usage: python3 script/pysol_deal.py 123456
+
+import random
+import sys
+
+N_PILES = 7
+SUITS = "CSHD"
+RANKS = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K"]
+
+
+def deal(seed):
+    # clubs, spades, hearts then diamonds, each from the ace to the king
+    cards = [r + s for s in SUITS for r in RANKS]
+
+    # `shuffle` of PySolFC, `random.random()` is the same for the seeds above 32000
+    rng = random.Random(seed)
+    for n in range(len(cards) - 1, 0, -1):
+        j = int(rng.random() * (n + 1))
+        cards[n], cards[j] = cards[j], cards[n]
+
+    # the cards are dealt from the end, the hidden layers from the last pile
+    # then the face up cards, the rest is the stock
+    cards.reverse()
+    piles = [[] for _ in range(N_PILES)]
+    for layer in range(N_PILES - 1):
+        for pile in range(N_PILES - 1, layer, -1):
+            piles[pile].append(cards.pop(0))
+    for pile in range(N_PILES - 1, -1, -1):
+        piles[pile].append(cards.pop(0))
+    return piles, cards
+
+
+if __name__ == "__main__":
+    piles, stock = deal(int(sys.argv[1]))
+    for pile in piles:
+        print(" ".join(pile))
+    print(" ".join(stock))
@@ -0,0 +1,227 @@
+//! Short codes to share a deal, e.g. `K3-4PePhQESEJFQKYxbqbrBGzPLpQ3k3ZqqmzeLFeGNh`
+//!
+//! A code is the rules (`K` for Klondike) with the draw step, then the exact permutation number
+//! (see `exact_shuffle`) in base 58 followed by two checksum characters.
+use core::{fmt, num::NonZeroU8, str::FromStr};
+
+use crate::shuffler::{encode_shuffle, exact_shuffle, CardDeck, U256};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+// the Bitcoin alphabet, without the characters easily mistaken for others (0, O, I, l)
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE: u32 = 58;
+// the largest prime below 58 * 58, so that any single wrong character
+// or swap of two adjacent characters changes the checksum
+const CHECK_MOD: u32 = 3361;
+const RULES: char = 'K';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DealCodeError {
+    /// The code doesn't have the shape `K<draw step>-<characters>`
+    InvalidFormat,
+    /// The checksum doesn't match, the code was probably mistyped
+    InvalidChecksum,
+    /// The number is not a permutation of the cards
+    InvalidDeal,
+}
+
+impl fmt::Display for DealCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidFormat => "invalid deal code",
+            Self::InvalidChecksum => "wrong checksum in deal code",
+            Self::InvalidDeal => "deal code is not a valid deal",
+        })
+    }
+}
+
+impl core::error::Error for DealCodeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DealCode {
+    /// The exact permutation number of the deal
+    pub exact: U256,
+    pub draw_step: NonZeroU8,
+}
+
+fn checksum(draw_step: NonZeroU8, digits: &[u8]) -> u32 {
+    core::iter::once(draw_step.get())
+        .chain(digits.iter().copied())
+        .zip(1..)
+        .fold(0, |acc, (v, w)| (acc + u32::from(v) * w) % CHECK_MOD)
+}
+
+impl DealCode {
+    /// Return `None` when the `cards` is not a valid `CardDeck`
+    #[must_use]
+    pub fn new(cards: CardDeck, draw_step: NonZeroU8) -> Option<Self> {
+        Some(Self {
+            exact: encode_shuffle(cards)?,
+            draw_step,
+        })
+    }
+
+    /// # Panics
+    ///
+    /// Never, the number of a parsed code is always a valid deal
+    #[must_use]
+    pub fn deck(&self) -> CardDeck {
+        exact_shuffle(self.exact).unwrap()
+    }
+
+    // the base 58 digits, most significant first
+    fn digits(&self) -> Vec<u8> {
+        let mut digits = Vec::new();
+        let mut n = self.exact;
+        loop {
+            #[allow(clippy::cast_possible_truncation)]
+            digits.push((n % BASE).as_u32() as u8);
+            n /= BASE;
+            if n.is_zero() {
+                break;
+            }
+        }
+        digits.reverse();
+        digits
+    }
+}
+
+impl fmt::Display for DealCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.digits();
+        let check = checksum(self.draw_step, &digits);
+        write!(f, "{RULES}{}-", self.draw_step)?;
+        #[allow(clippy::cast_possible_truncation)]
+        for d in digits
+            .iter()
+            .copied()
+            .chain([(check / BASE) as u8, (check % BASE) as u8])
+        {
+            write!(f, "{}", char::from(ALPHABET[usize::from(d)]))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a code as printed, the rules letter can be in either case
+impl FromStr for DealCode {
+    type Err = DealCodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, body) = s
+            .trim()
+            .split_once('-')
+            .ok_or(DealCodeError::InvalidFormat)?;
+        let draw_step = prefix
+            .strip_prefix(RULES)
+            .or_else(|| prefix.strip_prefix(RULES.to_ascii_lowercase()))
+            .and_then(|x| x.parse::<NonZeroU8>().ok())
+            .ok_or(DealCodeError::InvalidFormat)?;
+
+        let mut digits = body
+            .bytes()
+            .map(|c| ALPHABET.iter().position(|&x| x == c))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(DealCodeError::InvalidFormat)?;
+        if digits.len() < 3 {
+            return Err(DealCodeError::InvalidFormat);
+        }
+
+        let check = digits.split_off(digits.len() - 2);
+        #[allow(clippy::cast_possible_truncation)]
+        let digits: Vec<u8> = digits.into_iter().map(|d| d as u8).collect();
+        #[allow(clippy::cast_possible_truncation)]
+        if checksum(draw_step, &digits) != (check[0] as u32) * BASE + check[1] as u32 {
+            return Err(DealCodeError::InvalidChecksum);
+        }
+
+        let exact = digits.iter().try_fold(U256::zero(), |acc, &d| {
+            acc.checked_mul(BASE.into())?.checked_add(d.into())
+        });
+        let exact = exact.ok_or(DealCodeError::InvalidDeal)?;
+        exact_shuffle(exact).ok_or(DealCodeError::InvalidDeal)?;
+        Ok(Self { exact, draw_step })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shuffler::default_shuffle;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for seed in 0..100 {
+            let deck = default_shuffle(seed);
+            let code = DealCode::new(deck, NonZeroU8::new(3).unwrap()).unwrap();
+            let text = code.to_string();
+            assert!(text.starts_with("K3-"));
+            assert!(text.len() <= 3 + 39 + 2);
+
+            let parsed: DealCode = text.parse().unwrap();
+            assert_eq!(parsed, code);
+            assert_eq!(parsed.deck(), deck);
+        }
+
+        let code = DealCode {
+            exact: U256::zero(),
+            draw_step: NonZeroU8::new(1).unwrap(),
+        };
+        assert_eq!(code.to_string().parse::<DealCode>(), Ok(code));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let text = DealCode::new(default_shuffle(12), NonZeroU8::new(1).unwrap())
+            .unwrap()
+            .to_string();
+
+        // a single mistyped character and a swap of two characters
+        let mut typo = text.clone().into_bytes();
+        typo[10] = if typo[10] == b'2' { b'3' } else { b'2' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert_eq!(
+            typo.parse::<DealCode>(),
+            Err(DealCodeError::InvalidChecksum)
+        );
+
+        let mut swap = text.clone().into_bytes();
+        let i = (5..swap.len() - 1)
+            .find(|&i| swap[i] != swap[i + 1])
+            .unwrap();
+        swap.swap(i, i + 1);
+        let swap = String::from_utf8(swap).unwrap();
+        assert_eq!(
+            swap.parse::<DealCode>(),
+            Err(DealCodeError::InvalidChecksum)
+        );
+
+        assert_eq!(
+            text.replacen("K1", "K3", 1).parse::<DealCode>(),
+            Err(DealCodeError::InvalidChecksum)
+        );
+        assert_eq!(
+            "K1-0OIl".parse::<DealCode>(),
+            Err(DealCodeError::InvalidFormat)
+        );
+        assert_eq!(
+            "X1-abc".parse::<DealCode>(),
+            Err(DealCodeError::InvalidFormat)
+        );
+
+        // 58^40 is larger than 52!
+        let digits = [57u8; 40];
+        let check = checksum(NonZeroU8::new(1).unwrap(), &digits);
+        let mut big = String::from("K1-");
+        big.extend(digits.iter().map(|&d| char::from(ALPHABET[usize::from(d)])));
+        #[allow(clippy::cast_possible_truncation)]
+        big.extend([
+            char::from(ALPHABET[(check / BASE) as usize]),
+            char::from(ALPHABET[(check % BASE) as usize]),
+        ]);
+        assert_eq!(big.parse::<DealCode>(), Err(DealCodeError::InvalidDeal));
+    }
+}
@@ -46,6 +46,37 @@ impl Deck {
         }
     }
 
+    /// Build a deck from its remaining cards (the waste first then the stock) and the size of the waste
+    ///
+    /// Unlike `new`, the positions are relative to `cards` instead of the original deal
+    /// Return `None` if there are too many or duplicated cards or the offset is out of range
+    #[must_use]
+    pub fn from_parts(cards: &[Card], draw_step: NonZeroU8, offset: u8) -> Option<Self> {
+        if cards.len() > N_DECK_CARDS as usize || usize::from(offset) > cards.len() {
+            return None;
+        }
+
+        let mut map = [!0u8; N_CARDS as usize];
+        let mut seen = 0u64;
+        #[allow(clippy::cast_possible_truncation)]
+        for (i, c) in cards.iter().enumerate() {
+            if seen & c.mask() != 0 {
+                return None;
+            }
+            seen |= c.mask();
+            map[c.mask_index() as usize] = i as u8;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        Some(Self {
+            deck: cards.iter().copied().collect(),
+            draw_step,
+            draw_cur: offset,
+            mask: full_mask(cards.len() as u8) as u32,
+            map,
+        })
+    }
+
     #[must_use]
     pub const fn draw_step(&self) -> NonZeroU8 {
         self.draw_step
@@ -288,14 +319,9 @@ impl Deck {
             }
         }
 
-        let mut pos: u8 = 0;
-
-        for c in rev_map.into_iter().flatten() {
-            self.deck[pos as usize] = c;
-            pos += 1;
-        }
-
-        self.deck.truncate(pos as usize);
+        // the deck can have fewer cards than the decoded one
+        self.deck.clear();
+        self.deck.extend(rev_map.into_iter().flatten());
 
         self.set_offset(offset);
         self.mask = mask;
@@ -344,7 +370,7 @@ impl Deck {
 
 #[cfg(test)]
 mod tests {
-    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use rand::{rngs::SmallRng, RngExt, SeedableRng};
 
     use crate::shuffler::default_shuffle;
 
@@ -381,7 +407,7 @@ mod tests {
                 }
 
                 for filter in [false, true] {
-                    deck.iter_callback(filter, |pos, card| {
+                    let _ = deck.iter_callback(filter, |pos, card| {
                         assert_eq!(deck.peek(pos), card);
                         ControlFlow::<()>::Continue(())
                     });
@@ -1,4 +1,4 @@
-use core::mem::swap;
+use core::{mem::swap, ops::Range};
 
 use crate::{
     card::{Card, N_CARDS, N_SUITS},
@@ -8,6 +8,7 @@ use crate::{
 };
 
 extern crate alloc;
+use alloc::vec;
 use alloc::vec::Vec;
 use arrayvec::ArrayVec;
 
@@ -20,8 +21,12 @@ pub struct DependencyEngine {
     last_draw: usize,
     n_moves: usize,
     dep: Vec<(usize, usize)>,
+    progress: Vec<usize>,
 }
 
+/// Range of indices (into the list of moves done on the engine) of a group of moves
+pub type Phase = Range<usize>;
+
 impl From<Solitaire> for DependencyEngine {
     fn from(value: Solitaire) -> Self {
         Self::new(value)
@@ -66,6 +71,7 @@ impl DependencyEngine {
             last_draw: 0,
             n_moves: 0,
             dep: Vec::default(),
+            progress: Vec::default(),
         }
     }
 
@@ -145,6 +151,13 @@ impl DependencyEngine {
 
         let (_, (_, extra)) = self.state.do_move(m);
 
+        // a move is a progress when it draws from the deck or reveals something
+        if matches!(m, Move::DeckStack(_) | Move::DeckPile(_) | Move::Reveal(_))
+            || !matches!(extra, ExtraInfo::None)
+        {
+            self.progress.push(self.n_moves);
+        }
+
         match extra {
             ExtraInfo::Card(new) => {
                 self.cards_from[new.mask_index() as usize] = self.n_moves;
@@ -227,4 +240,119 @@ impl DependencyEngine {
     pub fn get(&self) -> &Vec<(usize, usize)> {
         &self.dep
     }
+
+    /// Split the moves done so far into phases
+    ///
+    /// A phase is a maximal run of consecutive moves where no move depends on a progress move
+    /// (a draw from the deck, a `Reveal` or any move uncovering a hidden card) of the same run,
+    /// and it ends with a progress move.
+    /// So every phase only builds on what the earlier phases unlocked.
+    /// The trailing moves after the last progress move are put in the last phase
+    #[must_use]
+    pub fn phases(&self) -> Vec<Phase> {
+        let mut is_progress = vec![false; self.n_moves + 1];
+        for &id in &self.progress {
+            is_progress[id] = true;
+        }
+
+        let mut parents: Vec<Vec<usize>> = vec![Vec::new(); self.n_moves + 1];
+        for &(from, to) in &self.dep {
+            if from != usize::MAX {
+                parents[to].push(from);
+            }
+        }
+
+        // the move ids are 1-based, 0 is the initial state
+        let mut phases = Vec::<Phase>::new();
+        let mut start = 1;
+        let mut last_progress = 0;
+
+        for id in 1..=self.n_moves {
+            if parents[id].iter().any(|&p| p >= start && is_progress[p]) {
+                phases.push(start - 1..last_progress);
+                start = last_progress + 1;
+            }
+            if is_progress[id] {
+                last_progress = id;
+            }
+        }
+
+        if start <= self.n_moves {
+            phases.push(start - 1..self.n_moves);
+        }
+        phases
+    }
+}
+
+/// Replay the `moves` from `state` and split them into phases (see [`DependencyEngine::phases`])
+///
+/// Return `None` when one of the moves is not valid
+#[must_use]
+pub fn split_phases(state: Solitaire, moves: &[Move]) -> Option<Vec<Phase>> {
+    let mut engine = DependencyEngine::new(state);
+    for &m in moves {
+        if !engine.do_move(m) {
+            return None;
+        }
+    }
+    Some(engine.phases())
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::{shuffler::default_shuffle, solver::solve};
+
+    use super::*;
+
+    #[test]
+    fn test_phases() {
+        for seed in 12..20 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+            let Some(moves) = solve(&mut game.clone()).1 else {
+                continue;
+            };
+
+            let phases = split_phases(game, &moves).unwrap();
+            assert!(phases.iter().cloned().flatten().eq(0..moves.len()));
+            for phase in &phases[..phases.len() - 1] {
+                assert!(!phase.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_phase_boundaries() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let moves = solve(&mut game.clone()).1.unwrap();
+
+        let mut engine = DependencyEngine::new(game.clone());
+        // the progress moves, by index in `moves`
+        let mut g = game;
+        let mut is_progress = Vec::new();
+        for &m in &moves {
+            assert!(engine.do_move(m));
+            let (_, (_, extra)) = g.do_move(m);
+            is_progress.push(
+                matches!(m, Move::DeckStack(_) | Move::DeckPile(_) | Move::Reveal(_))
+                    || !matches!(extra, ExtraInfo::None),
+            );
+        }
+
+        let phases = engine.phases();
+        assert!(phases.len() > 1);
+        for phase in &phases[..phases.len() - 1] {
+            assert!(is_progress[phase.end - 1]);
+        }
+
+        // no move depends on a progress move of its own phase (the move ids are 1-based)
+        for &(from, to) in engine.get() {
+            if from == usize::MAX || from == 0 || !is_progress[from - 1] {
+                continue;
+            }
+            let phase = phases.iter().find(|p| p.contains(&(to - 1))).unwrap();
+            assert!(!phase.contains(&(from - 1)), "{from} -> {to} in {phase:?}");
+        }
+    }
 }
@@ -0,0 +1,314 @@
+use core::{fmt, str::FromStr};
+
+use rand::Rng;
+
+use crate::{
+    convert::convert_moves,
+    hop_solver::list_moves,
+    moves::{Move, N_MOVES_MAX},
+    solver::{solve_with_tracking, HistoryVec, SearchResult},
+    standard::{Pos, StandardSolitaire},
+    state::Solitaire,
+    tracking::{StateLimit, TerminateSignal},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tier {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Unsolvable,
+    /// The search ran out of budget
+    Unknown,
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+            Self::Expert => "expert",
+            Self::Unsolvable => "unsolvable",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTier;
+
+impl fmt::Display for InvalidTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid difficulty tier")
+    }
+}
+
+impl core::error::Error for InvalidTier {}
+
+impl FromStr for Tier {
+    type Err = InvalidTier;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            "expert" => Ok(Self::Expert),
+            "unsolvable" => Ok(Self::Unsolvable),
+            "unknown" => Ok(Self::Unknown),
+            _ => Err(InvalidTier),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyConfig {
+    /// Maximum number of unique states for each search
+    pub max_states: usize,
+    /// Number of sampled hidden-card layouts for the HOP win rate (0 to disable it)
+    pub hop_samples: usize,
+    /// Maximum number of states for each HOP sample
+    pub hop_limit: usize,
+}
+
+impl Default for DifficultyConfig {
+    fn default() -> Self {
+        Self {
+            max_states: 1_000_000,
+            hop_samples: 0,
+            hop_limit: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyReport {
+    pub result: SearchResult,
+    /// Number of unique states visited before the solver stops
+    pub unique_states: usize,
+    /// Length of the first solution found by the solver, usually longer than the shortest one
+    ///
+    /// It depends on the move ordering of the solver, so it's not part of the score
+    pub found_solution_len: Option<usize>,
+    /// Number of times the stock is turned over in the solution found by the solver
+    pub redeals: Option<usize>,
+    pub root_moves: usize,
+    /// Number of first moves which keep the game solvable
+    pub solvable_root_moves: usize,
+    /// Number of first moves whose search ran out of budget
+    pub unknown_root_moves: usize,
+    /// Win rate of the HOP player, who doesn't know the hidden cards
+    pub hop_win_rate: Option<f64>,
+    /// Difficulty from 0 (easiest) to 100 (hardest), only meaningful for solvable games
+    pub score: f64,
+    pub tier: Tier,
+}
+
+/// Number of times the stock is turned over when playing `moves` from `game`
+///
+/// Return `None` if the moves are not valid
+#[must_use]
+pub fn count_redeals(game: &Solitaire, moves: &[Move]) -> Option<usize> {
+    let moves = convert_moves(&mut StandardSolitaire::from(game), moves).ok()?;
+    let mut standard = StandardSolitaire::from(game);
+
+    let mut redeals = 0;
+    for m in &moves {
+        let offset = standard.get_deck().get_offset();
+        standard.do_move(m).ok()?;
+        if m.from == Pos::Deck && m.to == Pos::Deck && standard.get_deck().get_offset() < offset {
+            redeals += 1;
+        }
+    }
+    Some(redeals)
+}
+
+fn clamp01(x: f64) -> f64 {
+    x.clamp(0., 1.)
+}
+
+// weighted combination of the signals, the ranges are picked from the default seeds
+fn compute_score(report: &DifficultyReport) -> f64 {
+    // the counts are far below 2^52, so they are exact as `f64`
+    #[allow(clippy::cast_precision_loss)]
+    let (redeals, solvable, known) = (
+        report.redeals.unwrap_or(0) as f64,
+        report.solvable_root_moves as f64,
+        (report.root_moves - report.unknown_root_moves).max(1) as f64,
+    );
+    let mut parts = [
+        // about 2^7 to 2^23 states
+        (
+            0.35,
+            clamp01((f64::from(report.unique_states.max(1).ilog2()) - 7.) / 16.),
+        ),
+        (0.15, clamp01((redeals - 5.) / 10.)),
+        // the first moves of unknown outcome are left out
+        (0.2, 1. - solvable / known),
+        (0.15, 1. - report.hop_win_rate.unwrap_or(0.)),
+    ];
+    if report.hop_win_rate.is_none() {
+        parts[3].0 = 0.;
+    }
+
+    let total: f64 = parts.iter().map(|x| x.0).sum();
+    parts.iter().map(|x| x.0 * x.1).sum::<f64>() / total * 100.
+}
+
+// the thresholds split the solvable deals of the first 100 default seeds (draw 3, default config)
+// into roughly equal tiers, `test_tiers` checks a few of them
+const fn score_to_tier(score: f64) -> Tier {
+    if score < 11. {
+        Tier::Easy
+    } else if score < 17. {
+        Tier::Medium
+    } else if score < 23. {
+        Tier::Hard
+    } else {
+        Tier::Expert
+    }
+}
+
+/// Rate the difficulty of `game` by combining several signals into a single tier
+///
+/// It runs the solver once for the game and once for each first move, each limited by `config.max_states`
+pub fn rate_difficulty<R: Rng, T: TerminateSignal>(
+    game: &Solitaire,
+    config: &DifficultyConfig,
+    rng: &mut R,
+    sign: &T,
+) -> DifficultyReport {
+    let budget = StateLimit::new(config.max_states, sign);
+    let (result, history) = solve_with_tracking(&mut game.clone(), &budget, &budget);
+
+    let mut report = DifficultyReport {
+        result,
+        unique_states: budget.states(),
+        found_solution_len: history.as_ref().map(HistoryVec::len),
+        redeals: history.as_ref().and_then(|h| count_redeals(game, h)),
+        root_moves: 0,
+        solvable_root_moves: 0,
+        unknown_root_moves: 0,
+        hop_win_rate: None,
+        score: 0.,
+        tier: Tier::Unknown,
+    };
+
+    match result {
+        SearchResult::Solved => {}
+        SearchResult::Unsolvable => {
+            report.tier = Tier::Unsolvable;
+            return report;
+        }
+        SearchResult::Terminated | SearchResult::Crashed => return report,
+    }
+
+    let moves = game.gen_moves::<false>().to_vec::<N_MOVES_MAX>();
+    report.root_moves = moves.len();
+    for m in moves {
+        let mut g = game.clone();
+        g.do_move(m);
+        let budget = StateLimit::new(config.max_states, sign);
+        match solve_with_tracking(&mut g, &budget, &budget).0 {
+            SearchResult::Solved => report.solvable_root_moves += 1,
+            SearchResult::Unsolvable => {}
+            SearchResult::Terminated | SearchResult::Crashed => report.unknown_root_moves += 1,
+        }
+    }
+
+    if config.hop_samples > 0 {
+        report.hop_win_rate = list_moves(
+            &mut game.clone(),
+            rng,
+            config.hop_samples,
+            config.hop_limit,
+            sign,
+        )
+        .iter()
+        .filter(|(_, res)| res.played > 0)
+        .map(|(_, res)| {
+            #[allow(clippy::cast_precision_loss)]
+            let (wins, played) = (res.wins as f64, res.played as f64);
+            wins / played
+        })
+        .max_by(f64::total_cmp);
+    }
+
+    report.score = compute_score(&report);
+    report.tier = score_to_tier(report.score);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use crate::{shuffler::default_shuffle, tracking::DefaultTerminateSignal};
+
+    use super::*;
+
+    #[test]
+    fn test_difficulty() {
+        let mut rng = SmallRng::seed_from_u64(14);
+        let config = DifficultyConfig {
+            max_states: 10_000,
+            ..Default::default()
+        };
+
+        let game = Solitaire::new(&default_shuffle(8), NonZeroU8::new(3).unwrap());
+        let report = rate_difficulty(&game, &config, &mut rng, &DefaultTerminateSignal {});
+        assert_eq!(report.tier, Tier::Unsolvable);
+
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let report = rate_difficulty(&game, &config, &mut rng, &DefaultTerminateSignal {});
+        assert_eq!(report.result, SearchResult::Solved);
+        assert!(report.solvable_root_moves > 0);
+        assert!(report.solvable_root_moves + report.unknown_root_moves <= report.root_moves);
+        assert!((0. ..=100.).contains(&report.score));
+        assert!(report.tier < Tier::Unsolvable);
+    }
+
+    #[test]
+    fn test_tiers() {
+        let mut rng = SmallRng::seed_from_u64(14);
+        let config = DifficultyConfig::default();
+
+        for (seed, tier) in [
+            (16, Tier::Easy),
+            (12, Tier::Medium),
+            (76, Tier::Hard),
+            (20, Tier::Expert),
+            (8, Tier::Unsolvable),
+        ] {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+            let report = rate_difficulty(&game, &config, &mut rng, &DefaultTerminateSignal {});
+            assert_eq!(report.tier, tier, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_root_moves() {
+        let mut report = DifficultyReport {
+            result: SearchResult::Solved,
+            unique_states: 1000,
+            found_solution_len: Some(100),
+            redeals: Some(3),
+            root_moves: 4,
+            solvable_root_moves: 2,
+            unknown_root_moves: 2,
+            hop_win_rate: None,
+            score: 0.,
+            tier: Tier::Unknown,
+        };
+        let score = compute_score(&report);
+
+        // the moves which ran out of budget don't count as unsolvable
+        report.root_moves = 2;
+        report.unknown_root_moves = 0;
+        assert!((compute_score(&report) - score).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,136 @@
+use hashbrown::HashSet;
+use rand::{prelude::IndexedRandom, Rng};
+
+use crate::{
+    moves::N_MOVES_MAX,
+    pruning::{FullPruner, Pruner},
+    solver::N_PLY_MAX,
+    state::{Encode, Solitaire},
+    utils::MixHasherBuilder,
+};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Default)]
+pub struct TreeEstimate {
+    pub n_probes: usize,
+    /// Estimated number of nodes at each depth
+    pub depth_nodes: Vec<f64>,
+    /// Number of probes ending at each depth
+    pub leaf_depths: Vec<usize>,
+}
+
+impl TreeEstimate {
+    /// Estimated number of nodes of the whole search tree
+    #[must_use]
+    pub fn nodes(&self) -> f64 {
+        self.depth_nodes.iter().sum()
+    }
+
+    #[must_use]
+    pub fn max_depth(&self) -> usize {
+        self.depth_nodes.len().saturating_sub(1)
+    }
+
+    /// Average depth of the sampled leaves
+    #[must_use]
+    pub fn mean_depth(&self) -> f64 {
+        let total: usize = self
+            .leaf_depths
+            .iter()
+            .enumerate()
+            .map(|(depth, &cnt)| depth * cnt)
+            .sum();
+        // the counts are far below 2^52, so they are exact as `f64`
+        #[allow(clippy::cast_precision_loss)]
+        let (total, n_probes) = (total as f64, self.n_probes.max(1) as f64);
+        total / n_probes
+    }
+}
+
+/// Estimate the size of the search tree of `game` with Knuth's Monte Carlo method
+///
+/// Each probe follows a random path from the root using the same moves as the solver
+/// (dominance moves and `FullPruner`), and weights the nodes by the product of the branching factors.
+/// The transposition table can't be sampled, so the estimate is an upper bound of what the solver visits
+pub fn estimate_tree<R: Rng>(game: &Solitaire, n_probes: usize, rng: &mut R) -> TreeEstimate {
+    let mut depth_nodes = Vec::<f64>::new();
+    let mut leaf_depths = Vec::<usize>::new();
+    let mut path = HashSet::<Encode, MixHasherBuilder>::default();
+
+    for _ in 0..n_probes {
+        let mut game = game.clone();
+        let mut pruner = FullPruner::default();
+        let mut weight = 1f64;
+        let mut depth = 0;
+        path.clear();
+
+        loop {
+            if depth_nodes.len() <= depth {
+                depth_nodes.push(0.);
+            }
+            depth_nodes[depth] += weight;
+
+            // a cycle is cut by the transposition table
+            if game.is_win() || !path.insert(game.encode()) || depth >= N_PLY_MAX {
+                break;
+            }
+
+            let moves = game
+                .gen_moves::<true>()
+                .filter(&pruner.prune_moves(&game))
+                .to_vec::<N_MOVES_MAX>();
+            let Some(&m) = moves.choose(rng) else {
+                break;
+            };
+
+            #[allow(clippy::cast_precision_loss)]
+            let n_moves = moves.len() as f64;
+            weight *= n_moves;
+            let (rev_m, (_, extra)) = game.do_move(m);
+            pruner = pruner.update(m, rev_m, extra);
+            depth += 1;
+        }
+
+        if leaf_depths.len() <= depth {
+            leaf_depths.resize(depth + 1, 0);
+        }
+        leaf_depths[depth] += 1;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let probes = n_probes.max(1) as f64;
+    for x in &mut depth_nodes {
+        *x /= probes;
+    }
+
+    TreeEstimate {
+        n_probes,
+        depth_nodes,
+        leaf_depths,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use crate::shuffler::default_shuffle;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let mut rng = SmallRng::seed_from_u64(14);
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+
+        let est = estimate_tree(&game, 100, &mut rng);
+        assert!((est.depth_nodes[0] - 1.).abs() < f64::EPSILON);
+        assert_eq!(est.leaf_depths.iter().sum::<usize>(), 100);
+        assert_eq!(est.leaf_depths.len(), est.depth_nodes.len());
+        assert!(est.nodes() >= est.depth_nodes.len() as f64);
+    }
+}
@@ -1,6 +1,8 @@
-use core::fmt;
+use core::{fmt, str::FromStr};
 
 use crate::card::{Card, N_RANKS, N_SUITS};
+use crate::deck::N_PILES;
+use crate::moves::Move;
 use crate::standard::{Pos, StandardMove};
 
 pub const SYMBOLS: [&str; N_SUITS as usize] = ["♥", "♦", "♣", "♠"];
@@ -34,3 +36,156 @@ impl fmt::Display for StandardMove {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError;
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid notation")
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+// the suit glyph or its letter (H, D, C, S in either case)
+fn parse_suit(c: char) -> Result<u8, ParseError> {
+    let pos = SYMBOLS
+        .iter()
+        .position(|s| s.starts_with(c))
+        .or_else(|| "HDCS".find(c.to_ascii_uppercase()))
+        .ok_or(ParseError)?;
+    u8::try_from(pos).map_err(|_| ParseError)
+}
+
+/// Parse a card as printed ("10♥") or with the suit letter ("10h", "QS")
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suit = s.chars().last().ok_or(ParseError)?;
+        let rank = &s[..s.len() - suit.len_utf8()];
+        let rank = NUMBERS
+            .iter()
+            .position(|x| x.eq_ignore_ascii_case(rank))
+            .ok_or(ParseError)?;
+        Ok(Self::new(
+            u8::try_from(rank).map_err(|_| ParseError)?,
+            parse_suit(suit)?,
+        ))
+    }
+}
+
+/// Parse a position as printed ("D", "♠", "3")
+///
+/// The foundation can also be written with the suit letter,
+/// but "D" is the deck so the diamond foundation is "d" or "♦"
+impl FromStr for Pos {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "D" {
+            return Ok(Self::Deck);
+        }
+        if let Ok(pile) = s.parse::<u8>() {
+            return if (1..=N_PILES).contains(&pile) {
+                Ok(Self::Pile(pile - 1))
+            } else {
+                Err(ParseError)
+            };
+        }
+        let mut chars = s.chars();
+        let suit = parse_suit(chars.next().ok_or(ParseError)?)?;
+        if chars.next().is_some() {
+            return Err(ParseError);
+        }
+        Ok(Self::Stack(suit))
+    }
+}
+
+/// Parse a move as printed ("DP 5♠")
+impl FromStr for Move {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, card) = s.trim().split_once(' ').ok_or(ParseError)?;
+        let card = card.trim().parse()?;
+        Ok(match kind.to_ascii_uppercase().as_str() {
+            "DS" => Self::DeckStack(card),
+            "PS" => Self::PileStack(card),
+            "DP" => Self::DeckPile(card),
+            "SP" => Self::StackPile(card),
+            "R" => Self::Reveal(card),
+            _ => return Err(ParseError),
+        })
+    }
+}
+
+/// Parse a standard move as printed ("5♠:3▸♠", "=") or with ">" as the arrow
+impl FromStr for StandardMove {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "=" {
+            return Ok(Self::DRAW_NEXT);
+        }
+        let (card, rest) = s.split_once(':').ok_or(ParseError)?;
+        let (from, to) = rest
+            .split_once('▸')
+            .or_else(|| rest.split_once('>'))
+            .ok_or(ParseError)?;
+        Ok(Self::new(from.parse()?, to.parse()?, card.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::{
+        convert::convert_moves, shuffler::default_shuffle, solver::solve,
+        standard::StandardSolitaire, state::Solitaire,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_parse_card() {
+        for rank in 0..N_RANKS {
+            for suit in 0..N_SUITS {
+                let c = Card::new(rank, suit);
+                assert_eq!(c.to_string().parse(), Ok(c));
+            }
+        }
+        assert_eq!("10h".parse(), Ok(Card::new(9, 0)));
+        assert_eq!("qS".parse(), Ok(Card::new(11, 3)));
+        assert_eq!("1♥".parse::<Card>(), Err(ParseError));
+        assert_eq!("Ax".parse::<Card>(), Err(ParseError));
+        assert_eq!("".parse::<Card>(), Err(ParseError));
+
+        assert_eq!("D".parse(), Ok(Pos::Deck));
+        assert_eq!("d".parse(), Ok(Pos::Stack(1)));
+        assert_eq!("S".parse(), Ok(Pos::Stack(3)));
+        assert_eq!("8".parse::<Pos>(), Err(ParseError));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let moves = solve(&mut game.clone()).1.unwrap();
+        for m in &moves {
+            assert_eq!(m.to_string().parse(), Ok(*m));
+        }
+
+        let standard = convert_moves(&mut StandardSolitaire::from(&game), &moves).unwrap();
+        for m in &standard {
+            assert!(m.to_string().parse::<StandardMove>().unwrap() == *m);
+        }
+        assert!(
+            "5s:3>d".parse::<StandardMove>().unwrap()
+                == StandardMove::new(Pos::Pile(2), Pos::Stack(1), Card::new(4, 3))
+        );
+        assert!("5s:3-d".parse::<StandardMove>().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use core::{num::NonZeroU8, ops::RangeInclusive};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    difficulty::{rate_difficulty, DifficultyConfig, Tier},
+    shuffler::{encode_shuffle, exact_shuffle, CardDeck, U256},
+    solver::{solve_with_tracking, HistoryVec, SearchResult},
+    state::Solitaire,
+    tracking::{StateLimit, TerminateSignal},
+};
+
+// 52! < 2^226 so most of the random numbers in this range are valid seeds
+const SEED_BITS: usize = 226;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    pub draw_step: NonZeroU8,
+    /// Maximum number of unique states for solving each deal
+    pub max_states: usize,
+    /// Maximum number of deals tried before giving up
+    pub max_attempts: usize,
+    /// Only accept the deals with the difficulty tier in this range
+    pub band: Option<RangeInclusive<Tier>>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            draw_step: NonZeroU8::new(3).unwrap(),
+            max_states: 1_000_000,
+            max_attempts: 1000,
+            band: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedDeal {
+    pub deck: CardDeck,
+    /// The exact seed of the deal (`exact_shuffle(seed) == deck`)
+    pub seed: U256,
+    pub solution: HistoryVec,
+    /// Only rated when a difficulty band is given
+    pub tier: Option<Tier>,
+    /// Number of deals tried
+    pub attempts: usize,
+}
+
+fn random_deck<R: Rng>(rng: &mut R) -> CardDeck {
+    loop {
+        let seed = U256([
+            rng.next_u64(),
+            rng.next_u64(),
+            rng.next_u64(),
+            rng.next_u64(),
+        ]) & ((U256::one() << SEED_BITS) - 1);
+        if let Some(deck) = exact_shuffle(seed) {
+            return deck;
+        }
+    }
+}
+
+/// Draw random deals until one is proven solvable (and in the difficulty band if given)
+///
+/// The solver is bounded by the number of states rather than time so the result only depends on `generator_seed`
+/// Return `None` if no deal is found in `config.max_attempts` tries or it's terminated
+/// # Panics
+///
+/// Never (unless buggy)
+pub fn generate_solvable<T: TerminateSignal>(
+    generator_seed: u64,
+    config: &GeneratorConfig,
+    sign: &T,
+) -> Option<GeneratedDeal> {
+    let mut rng = SmallRng::seed_from_u64(generator_seed);
+
+    for attempts in 1..=config.max_attempts {
+        if sign.is_terminated() {
+            return None;
+        }
+
+        let deck = random_deck(&mut rng);
+        let game = Solitaire::new(&deck, config.draw_step);
+
+        let limit = StateLimit::new(config.max_states, sign);
+        let (res, solution) = solve_with_tracking(&mut game.clone(), &limit, &limit);
+        if res != SearchResult::Solved {
+            continue;
+        }
+
+        let tier = if let Some(band) = &config.band {
+            let report = rate_difficulty(
+                &game,
+                &DifficultyConfig {
+                    max_states: config.max_states,
+                    ..Default::default()
+                },
+                &mut rng,
+                sign,
+            );
+            if !band.contains(&report.tier) {
+                continue;
+            }
+            Some(report.tier)
+        } else {
+            None
+        };
+
+        return Some(GeneratedDeal {
+            deck,
+            seed: encode_shuffle(deck).unwrap(),
+            solution: solution.unwrap(),
+            tier,
+            attempts,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tracking::DefaultTerminateSignal;
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let config = GeneratorConfig {
+            max_states: 10_000,
+            ..Default::default()
+        };
+
+        let deal = generate_solvable(14, &config, &DefaultTerminateSignal {}).unwrap();
+        assert_eq!(exact_shuffle(deal.seed), Some(deal.deck));
+        assert_eq!(
+            generate_solvable(14, &config, &DefaultTerminateSignal {}),
+            Some(deal.clone())
+        );
+
+        let mut game = Solitaire::new(&deal.deck, config.draw_step);
+        for m in deal.solution {
+            game.do_move(m);
+        }
+        assert!(game.is_win());
+    }
+}
@@ -0,0 +1,345 @@
+use alloc::collections::VecDeque;
+use hashbrown::HashMap;
+
+use crate::{
+    card::{KING_MASK, N_RANKS},
+    deck::{N_DECK_CARDS, N_PILES},
+    moves::{Move, MoveMask, N_MOVES_MAX},
+    pruning::FullPruner,
+    solver::{HistoryVec, SearchResult},
+    state::{Encode, Solitaire},
+    tracking::{DefaultTerminateSignal, EmptySearchStats, SearchStatistics, TerminateSignal},
+    traverse::{traverse, Callback, Control, TpTable},
+    utils::MixHasherBuilder,
+};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+/// All the cards of the `suit` are moved to the foundation
+pub fn suit_cleared(suit: u8) -> impl Fn(&Solitaire) -> bool {
+    move |game| game.get_stack().get(suit) == N_RANKS
+}
+
+/// There is no face-down card left in the tableau
+#[must_use]
+pub fn all_revealed(game: &Solitaire) -> bool {
+    game.get_hidden().total_down_cards() == 0
+}
+
+/// At least `n_kings` kings are moved into an empty pile
+///
+/// The kings dealt at the base of a pile don't count, even with nothing hidden under them
+pub fn kings_on_empty(n_kings: u8) -> impl Fn(&Solitaire) -> bool {
+    move |game| {
+        // the locked cards are still where they are dealt
+        let moved = game.get_visible_mask() & !game.get_locked_mask() & KING_MASK;
+        moved.count_ones() >= u32::from(n_kings)
+    }
+}
+
+struct GoalCallback<'a, G: Fn(&Solitaire) -> bool, S: SearchStatistics, T: TerminateSignal> {
+    goal: G,
+    history: HistoryVec,
+    stats: &'a S,
+    sign: &'a T,
+    result: SearchResult,
+}
+
+impl<G: Fn(&Solitaire) -> bool, S: SearchStatistics, T: TerminateSignal> Callback
+    for GoalCallback<'_, G, S, T>
+{
+    type Pruner = FullPruner;
+    const DOMINANCE: bool = false;
+
+    fn is_goal(&self, game: &Solitaire) -> bool {
+        (self.goal)(game)
+    }
+
+    fn on_win(&mut self, _: &Solitaire) -> Control {
+        self.result = SearchResult::Solved;
+        Control::Halt
+    }
+
+    fn on_visit(&mut self, _: &Solitaire, _: Encode) -> Control {
+        if self.sign.is_terminated() {
+            self.result = SearchResult::Terminated;
+            return Control::Halt;
+        }
+
+        self.stats.hit_a_state(self.history.len());
+        Control::Ok
+    }
+
+    fn on_move_gen(&mut self, m: &MoveMask, _: Encode) -> Control {
+        self.stats.hit_unique_state(self.history.len(), m.len());
+        Control::Ok
+    }
+
+    fn on_do_move(&mut self, _: &Solitaire, m: Move, _: Encode, _: &FullPruner) -> Control {
+        self.history.push(m);
+        Control::Ok
+    }
+
+    fn on_undo_move(&mut self, _: Move, _: Encode, res: &Control) {
+        if *res == Control::Ok {
+            self.history.pop();
+        }
+        self.stats.finish_move(self.history.len());
+    }
+}
+
+/// Find the first line reaching a state satisfying `goal` (instead of winning the game)
+///
+/// The dominance moves are disabled since they assume the goal is winning the game
+/// `SearchResult::Solved` means the goal is reached, `SearchResult::Unsolvable` means it can't be
+pub fn solve_goal_with_tracking<
+    G: Fn(&Solitaire) -> bool,
+    S: SearchStatistics,
+    T: TerminateSignal,
+>(
+    game: &mut Solitaire,
+    goal: G,
+    stats: &S,
+    sign: &T,
+) -> (SearchResult, Option<HistoryVec>) {
+    let mut tp = TpTable::default();
+
+    let mut callback = GoalCallback {
+        goal,
+        history: HistoryVec::new(),
+        stats,
+        sign,
+        result: SearchResult::Unsolvable,
+    };
+
+    traverse(game, FullPruner::default(), &mut tp, &mut callback);
+
+    let result = callback.result;
+
+    if result == SearchResult::Solved {
+        (result, Some(callback.history))
+    } else {
+        (result, None)
+    }
+}
+
+pub fn solve_goal<G: Fn(&Solitaire) -> bool>(
+    game: &mut Solitaire,
+    goal: G,
+) -> (SearchResult, Option<HistoryVec>) {
+    solve_goal_with_tracking(game, goal, &EmptySearchStats {}, &DefaultTerminateSignal {})
+}
+
+/// Find the shortest line reaching a state satisfying `goal` using breadth-first search
+///
+/// It stores every visited state so it uses much more memory than `solve_goal`,
+/// and there is no state limit: only `sign` bounds the search
+pub fn shortest_goal<G: Fn(&Solitaire) -> bool, T: TerminateSignal>(
+    game: &Solitaire,
+    goal: G,
+    sign: &T,
+) -> (SearchResult, Option<HistoryVec>) {
+    bfs(game, goal, |_| true, sign)
+}
+
+// the tableau cards are only revealed and the cards are only taken out of the deck,
+// so `to` is unreachable when it has more hidden cards in a pile or a card back in the deck
+// (the foundation isn't checked since its cards can be moved back to the tableau)
+fn may_reach(from: &Solitaire, to: &Solitaire) -> bool {
+    const DECK_MASK: u32 = (1 << N_DECK_CARDS) - 1;
+    let deck_from = from.get_deck().encode() & DECK_MASK;
+    let deck_to = to.get_deck().encode() & DECK_MASK;
+    deck_to & !deck_from == 0
+        && (0..N_PILES).all(|pos| to.get_hidden().len(pos) <= from.get_hidden().len(pos))
+}
+
+// breadth-first search which doesn't expand the states failing `expand`
+fn bfs<G: Fn(&Solitaire) -> bool, E: Fn(&Solitaire) -> bool, T: TerminateSignal>(
+    game: &Solitaire,
+    goal: G,
+    expand: E,
+    sign: &T,
+) -> (SearchResult, Option<HistoryVec>) {
+    if goal(game) {
+        return (SearchResult::Solved, Some(HistoryVec::new()));
+    }
+
+    let mut game = game.clone();
+    let root = game.encode();
+
+    let mut parent = HashMap::<Encode, Option<(Encode, Move)>, MixHasherBuilder>::default();
+    parent.insert(root, None);
+
+    let mut queue = VecDeque::<Encode>::new();
+    queue.push_back(root);
+
+    while let Some(encode) = queue.pop_front() {
+        if sign.is_terminated() {
+            return (SearchResult::Terminated, None);
+        }
+
+        game.decode(encode);
+        for m in game.gen_moves::<false>().to_vec::<N_MOVES_MAX>() {
+            let (_, (undo, _)) = game.do_move(m);
+            let next = game.encode();
+
+            if !parent.contains_key(&next) {
+                parent.insert(next, Some((encode, m)));
+
+                if goal(&game) {
+                    let mut line = Vec::<Move>::new();
+                    let mut cur = next;
+                    while let Some((prev, m)) = parent[&cur] {
+                        line.push(m);
+                        cur = prev;
+                    }
+                    return (SearchResult::Solved, Some(line.into_iter().rev().collect()));
+                }
+                if expand(&game) {
+                    queue.push_back(next);
+                }
+            }
+
+            game.undo_move(m, undo);
+        }
+    }
+
+    (SearchResult::Unsolvable, None)
+}
+
+/// Find the shortest move sequence from `game` to the state with the encoding `target`
+///
+/// Both states must come from the same deal since the encoding is relative to it.
+/// `SearchResult::Unsolvable` means the target is unreachable.
+/// The states which reveal a card still hidden in the target or take out of the deck a card
+/// still in it are not expanded (and an unreachable target this way is rejected right away),
+/// but there is no state limit: only `sign` bounds the search
+pub fn find_path<T: TerminateSignal>(
+    game: &Solitaire,
+    target: Encode,
+    sign: &T,
+) -> (SearchResult, Option<HistoryVec>) {
+    let mut to = game.clone();
+    to.decode(target);
+    if !may_reach(game, &to) {
+        return (SearchResult::Unsolvable, None);
+    }
+    bfs(game, |g| g.encode() == target, |g| may_reach(g, &to), sign)
+}
+
+/// Same as `find_path` but with the target position instead of its encoding
+pub fn find_path_to<T: TerminateSignal>(
+    game: &Solitaire,
+    target: &Solitaire,
+    sign: &T,
+) -> (SearchResult, Option<HistoryVec>) {
+    find_path(game, target.encode(), sign)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use rand::{prelude::IndexedRandom, rngs::SmallRng, SeedableRng};
+
+    use crate::{shuffler::default_shuffle, solver::solve};
+
+    use super::*;
+
+    #[test]
+    fn test_goal() {
+        for seed in 12..16 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+            let goal = |g: &Solitaire| g.get_stack().len() >= 3;
+
+            let (res, line) = solve_goal(&mut game.clone(), goal);
+            let (res_short, line_short) = shortest_goal(&game, goal, &DefaultTerminateSignal {});
+            assert_eq!(res, res_short);
+
+            let (Some(line), Some(line_short)) = (line, line_short) else {
+                continue;
+            };
+            assert!(line_short.len() <= line.len());
+
+            for line in [line, line_short] {
+                let mut g = game.clone();
+                for m in line {
+                    assert!(MoveMask::from(m).filter(&g.gen_moves::<false>()).is_empty());
+                    g.do_move(m);
+                }
+                assert!(goal(&g));
+            }
+        }
+    }
+
+    #[test]
+    fn test_kings_on_empty() {
+        // a deal with a king alone in the first pile
+        let game = (0..)
+            .map(|seed| Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap()))
+            .find(|g| g.get_hidden().get(0)[0].is_king())
+            .unwrap();
+        assert!(!kings_on_empty(1)(&game));
+
+        let (res, line) = solve_goal(&mut game.clone(), kings_on_empty(1));
+        assert_eq!(res, SearchResult::Solved);
+        let line = line.unwrap();
+        let mut g = game.clone();
+        for &m in &line[..line.len() - 1] {
+            g.do_move(m);
+        }
+        assert!(!kings_on_empty(1)(&g));
+        assert!(line.last().unwrap().card().is_king());
+        g.do_move(*line.last().unwrap());
+        assert!(kings_on_empty(1)(&g));
+    }
+
+    #[test]
+    fn test_unreachable_path() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let moves = solve(&mut game.clone()).1.unwrap();
+
+        // a card is revealed or taken out of the deck by the first move already
+        let mut ahead = game.clone();
+        ahead.do_move(moves[0]);
+        let (res, line) = find_path_to(&ahead, &game, &DefaultTerminateSignal {});
+        assert_eq!(res, SearchResult::Unsolvable);
+        assert!(line.is_none());
+
+        let (res, line) = find_path_to(&game, &ahead, &DefaultTerminateSignal {});
+        assert_eq!(res, SearchResult::Solved);
+        assert_eq!(line.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_find_path() {
+        let mut rng = SmallRng::seed_from_u64(14);
+
+        for seed in 12..16 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+
+            let mut target = game.clone();
+            let mut n_moves = 0;
+            for _ in 0..10 {
+                let moves = target.gen_moves::<false>().to_vec::<N_MOVES_MAX>();
+                let Some(&m) = moves.choose(&mut rng) else {
+                    break;
+                };
+                target.do_move(m);
+                n_moves += 1;
+            }
+
+            let (res, line) = find_path_to(&game, &target, &DefaultTerminateSignal {});
+            assert_eq!(res, SearchResult::Solved);
+            let line = line.unwrap();
+            assert!(line.len() <= n_moves);
+
+            let mut g = game.clone();
+            for m in line {
+                g.do_move(m);
+            }
+            assert_eq!(g.encode(), target.encode());
+        }
+    }
+}
@@ -48,6 +48,7 @@ impl<T: TerminateSignal> Callback for HOPSolverCallback<'_, T> {
 }
 
 #[derive(Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HopResult {
     pub wins: usize,
     pub skips: usize,
@@ -0,0 +1,474 @@
+use core::{
+    fmt,
+    mem::{replace, take},
+};
+
+use crate::{
+    card::{Card, N_SUITS},
+    deck::N_PILES,
+    engine::MoveVec,
+    moves::Move,
+    pruning::{FullPruner, Pruner},
+    solver::{HistoryVec, SearchResult},
+    state::{Solitaire, UndoInfo},
+    tracking::{DefaultTerminateSignal, EmptySearchStats, SearchStatistics, TerminateSignal},
+    traverse::TpTable,
+};
+
+extern crate alloc;
+use alloc::{vec, vec::Vec};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Progress {
+    /// Ran out of budget (or got terminated), call `step` again to continue
+    Pending,
+    Done(SearchResult),
+}
+
+const MAGIC: &[u8; 4] = b"LBCP";
+const VERSION: u8 = 1;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CheckpointError {
+    /// Not a checkpoint file or it is truncated
+    InvalidFormat,
+    UnsupportedVersion(u8),
+    /// The checkpoint was made from another game
+    MismatchedGame,
+    /// The saved search doesn't replay on the game
+    Corrupted,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "invalid checkpoint format"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported checkpoint version {v}"),
+            Self::MismatchedGame => write!(f, "checkpoint was made from another game"),
+            Self::Corrupted => write!(f, "corrupted checkpoint"),
+        }
+    }
+}
+
+const fn card_value(c: Card) -> u8 {
+    let (rank, suit) = c.split();
+    rank * N_SUITS + suit
+}
+
+// identify the starting position of the search, the encoding alone is relative to the deal
+fn fingerprint(game: &Solitaire) -> Vec<u8> {
+    let mut res = vec![game.get_deck().draw_step().get()];
+    for pos in 0..N_PILES {
+        let cards = game.get_hidden().get(pos);
+        // a pile has at most 7 cards in its hidden part
+        #[allow(clippy::cast_possible_truncation)]
+        res.push(cards.len() as u8);
+        res.extend(cards.iter().map(|&c| card_value(c)));
+    }
+    res.push(game.get_deck().len());
+    res.extend(game.get_deck().iter().map(card_value));
+    res.extend(game.encode().to_le_bytes());
+    res
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CheckpointError> {
+        if self.0.len() < n {
+            return Err(CheckpointError::InvalidFormat);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], CheckpointError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+struct Frame {
+    moves: MoveVec,
+    next: usize,
+    pruner: FullPruner,
+}
+
+/// The same search as `solve_with_tracking` but with an explicit stack
+/// so it can be run a bounded amount of work at a time and resumed later
+pub struct IncrementalSolver {
+    root: Vec<u8>,
+    game: Solitaire,
+    tp: TpTable,
+    frames: Vec<Frame>,
+    history: HistoryVec,
+    undo: Vec<UndoInfo>,
+    started: bool,
+    result: Option<SearchResult>,
+}
+
+impl From<Solitaire> for IncrementalSolver {
+    fn from(value: Solitaire) -> Self {
+        Self::new(value)
+    }
+}
+
+impl IncrementalSolver {
+    #[must_use]
+    pub fn new(game: Solitaire) -> Self {
+        Self {
+            root: fingerprint(&game),
+            game,
+            tp: TpTable::default(),
+            frames: Vec::default(),
+            history: HistoryVec::new(),
+            undo: Vec::default(),
+            started: false,
+            result: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn state(&self) -> &Solitaire {
+        &self.game
+    }
+
+    /// The current search path (the solution when it's solved)
+    #[must_use]
+    pub const fn history(&self) -> &HistoryVec {
+        &self.history
+    }
+
+    #[must_use]
+    pub const fn result(&self) -> Option<SearchResult> {
+        self.result
+    }
+
+    /// Same output as `solve_with_tracking`, it's `SearchResult::Terminated` if the search is not finished
+    #[must_use]
+    pub fn into_result(self) -> (SearchResult, Option<HistoryVec>) {
+        match self.result {
+            Some(SearchResult::Solved) => (SearchResult::Solved, Some(self.history)),
+            Some(res) => (res, None),
+            None => (SearchResult::Terminated, None),
+        }
+    }
+
+    // return true if the state need to be expanded
+    fn visit<S: SearchStatistics>(&mut self, pruner: FullPruner, stats: &S) -> bool {
+        if self.game.is_win() {
+            self.result = Some(SearchResult::Solved);
+            return false;
+        }
+
+        let encode = self.game.encode();
+        stats.hit_a_state(self.history.len());
+
+        if !self.tp.insert(encode) {
+            return false;
+        }
+
+        let move_list = self
+            .game
+            .gen_moves::<true>()
+            .filter(&pruner.prune_moves(&self.game));
+        stats.hit_unique_state(self.history.len(), move_list.len());
+
+        self.frames.push(Frame {
+            moves: move_list.to_vec(),
+            next: 0,
+            pruner,
+        });
+        true
+    }
+
+    /// # Panics
+    ///
+    /// Never (unless buggy)
+    fn backtrack<S: SearchStatistics>(&mut self, stats: &S) {
+        let m = self.history.pop().unwrap();
+        let undo = self.undo.pop().unwrap();
+        self.game.undo_move(m, undo);
+        stats.finish_move(self.history.len());
+    }
+
+    /// Visit at most `max_nodes` states then return
+    ///
+    /// When terminated by `sign`, it returns `Progress::Pending` and the search can still be resumed
+    pub fn step_with_tracking<S: SearchStatistics, T: TerminateSignal>(
+        &mut self,
+        max_nodes: usize,
+        stats: &S,
+        sign: &T,
+    ) -> Progress {
+        let mut n_nodes = 0;
+
+        loop {
+            if let Some(res) = self.result {
+                return Progress::Done(res);
+            }
+
+            if n_nodes >= max_nodes || sign.is_terminated() {
+                return Progress::Pending;
+            }
+            n_nodes += 1;
+
+            if !self.started {
+                self.started = true;
+                if !self.visit(FullPruner::default(), stats) && self.result.is_none() {
+                    self.result = Some(SearchResult::Unsolvable);
+                }
+                continue;
+            }
+
+            let Some(frame) = self.frames.last_mut() else {
+                self.result = Some(SearchResult::Unsolvable);
+                continue;
+            };
+
+            if let Some(&m) = frame.moves.get(frame.next) {
+                frame.next += 1;
+
+                let (rev_m, (undo, extra)) = self.game.do_move(m);
+                let pruner = frame.pruner.update(m, rev_m, extra);
+                self.history.push(m);
+                self.undo.push(undo);
+
+                if !self.visit(pruner, stats) && self.result.is_none() {
+                    self.backtrack(stats);
+                }
+            } else {
+                self.frames.pop();
+                if self.frames.is_empty() {
+                    self.result = Some(SearchResult::Unsolvable);
+                } else {
+                    self.backtrack(stats);
+                }
+            }
+        }
+    }
+
+    pub fn step(&mut self, max_nodes: usize) -> Progress {
+        self.step_with_tracking(max_nodes, &EmptySearchStats {}, &DefaultTerminateSignal {})
+    }
+
+    /// Serialize the whole search (stack, history and transposition table) into a versioned binary format
+    ///
+    /// The game itself is not saved, only a fingerprint of it to check when loading.
+    /// It holds a copy of the whole transposition table, `save_chunks` doesn't
+    #[must_use]
+    pub fn save(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(self.tp.len() * 8 + 64);
+        for chunk in self.save_chunks() {
+            res.extend(chunk);
+        }
+        res
+    }
+
+    /// Same as `save` but in pieces to write one after the other: the header with the stack and
+    /// the history, then the transposition table a few thousand states at a time
+    pub fn save_chunks(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        const CHUNK_STATES: usize = 1 << 13;
+
+        let mut res = Vec::new();
+        res.extend(MAGIC);
+        res.push(VERSION);
+
+        // the fingerprint has at most 1 + 7 * 8 + 1 + 24 bytes
+        #[allow(clippy::cast_possible_truncation)]
+        res.extend((self.root.len() as u16).to_le_bytes());
+        res.extend(&self.root);
+
+        res.push(match self.result {
+            None if !self.started => 0,
+            None => 1,
+            Some(SearchResult::Solved) => 2,
+            Some(_) => 3,
+        });
+
+        // the history and the stack are bounded by `N_PLY_MAX`, and a frame has fewer moves than `N_MOVES_MAX`
+        #[allow(clippy::cast_possible_truncation)]
+        res.extend((self.history.len() as u16).to_le_bytes());
+        for m in &self.history {
+            res.extend(m.code().to_le_bytes());
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        res.extend((self.frames.len() as u16).to_le_bytes());
+        #[allow(clippy::cast_possible_truncation)]
+        res.extend(self.frames.iter().map(|f| f.next as u8));
+
+        res.extend((self.tp.len() as u64).to_le_bytes());
+
+        let mut states = self.tp.iter();
+        core::iter::once(res).chain(core::iter::from_fn(move || {
+            let chunk: Vec<u8> = states
+                .by_ref()
+                .take(CHUNK_STATES)
+                .flat_map(|encode| encode.to_le_bytes())
+                .collect();
+            (!chunk.is_empty()).then_some(chunk)
+        }))
+    }
+
+    /// Restore a search saved by `save`, `game` must be the same starting state given to `new`
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the data is malformed or doesn't belong to `game`
+    pub fn load(mut game: Solitaire, data: &[u8]) -> Result<Self, CheckpointError> {
+        let mut reader = Reader(data);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(CheckpointError::InvalidFormat);
+        }
+        let [version] = reader.read()?;
+        if version != VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+
+        let root = fingerprint(&game);
+        let root_len = u16::from_le_bytes(reader.read()?);
+        if reader.take(root_len.into())? != root {
+            return Err(CheckpointError::MismatchedGame);
+        }
+
+        let [status] = reader.read()?;
+        let (started, result) = match status {
+            0 => (false, None),
+            1 => (true, None),
+            2 => (true, Some(SearchResult::Solved)),
+            3 => (true, Some(SearchResult::Unsolvable)),
+            _ => return Err(CheckpointError::Corrupted),
+        };
+
+        let n_moves = u16::from_le_bytes(reader.read()?);
+        let mut history = HistoryVec::new();
+        for _ in 0..n_moves {
+            let m = Move::from_code(u16::from_le_bytes(reader.read()?))
+                .ok_or(CheckpointError::Corrupted)?;
+            history
+                .try_push(m)
+                .map_err(|_| CheckpointError::Corrupted)?;
+        }
+
+        let n_frames = usize::from(u16::from_le_bytes(reader.read()?));
+        let next = reader.take(n_frames)?;
+        if n_frames != history.len() && n_frames != history.len() + 1 {
+            return Err(CheckpointError::Corrupted);
+        }
+
+        // the frames are regenerated by replaying the history
+        let mut frames = Vec::<Frame>::with_capacity(n_frames);
+        let mut undo = Vec::<UndoInfo>::with_capacity(history.len());
+        let mut pruner = FullPruner::default();
+        for (i, &next) in next.iter().enumerate() {
+            let moves = game
+                .gen_moves::<true>()
+                .filter(&pruner.prune_moves(&game))
+                .to_vec();
+            let next = usize::from(next);
+            if next > moves.len() {
+                return Err(CheckpointError::Corrupted);
+            }
+
+            if let Some(&m) = history.get(i) {
+                if next == 0 || moves[next - 1] != m {
+                    return Err(CheckpointError::Corrupted);
+                }
+                let (rev_m, (info, extra)) = game.do_move(m);
+                undo.push(info);
+                let child = pruner.update(m, rev_m, extra);
+                frames.push(Frame {
+                    moves,
+                    next,
+                    pruner: replace(&mut pruner, child),
+                });
+            } else {
+                frames.push(Frame {
+                    moves,
+                    next,
+                    pruner: take(&mut pruner),
+                });
+            }
+        }
+
+        let n_states = u64::from_le_bytes(reader.read()?);
+        let mut tp = TpTable::default();
+        for _ in 0..n_states {
+            tp.insert(u64::from_le_bytes(reader.read()?));
+        }
+
+        if !reader.0.is_empty() {
+            return Err(CheckpointError::InvalidFormat);
+        }
+
+        Ok(Self {
+            root,
+            game,
+            tp,
+            frames,
+            history,
+            undo,
+            started,
+            result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::{shuffler::default_shuffle, solver::solve};
+
+    use super::*;
+
+    #[test]
+    fn test_incremental() {
+        for seed in 0..10 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+
+            let mut solver = IncrementalSolver::new(game.clone());
+            let mut n_steps = 0;
+            while solver.step(100) == Progress::Pending {
+                n_steps += 1;
+                assert!(n_steps < 1_000_000);
+            }
+
+            assert_eq!(solver.into_result(), solve(&mut game.clone()));
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        for seed in 0..4 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+
+            let mut solver = IncrementalSolver::new(game.clone());
+            loop {
+                let data = solver.save();
+                assert_eq!(solver.save_chunks().flatten().collect::<Vec<_>>(), data);
+                solver = IncrementalSolver::load(game.clone(), &data).unwrap();
+                assert_eq!(solver.save().len(), data.len());
+
+                if solver.step(1000) != Progress::Pending {
+                    break;
+                }
+            }
+
+            assert_eq!(solver.into_result(), solve(&mut game.clone()));
+        }
+
+        let game = Solitaire::new(&default_shuffle(0), NonZeroU8::new(3).unwrap());
+        let other = Solitaire::new(&default_shuffle(1), NonZeroU8::new(3).unwrap());
+        let data = IncrementalSolver::new(game).save();
+        assert_eq!(
+            IncrementalSolver::load(other.clone(), &data).err(),
+            Some(CheckpointError::MismatchedGame)
+        );
+        assert_eq!(
+            IncrementalSolver::load(other.clone(), &data[..3]).err(),
+            Some(CheckpointError::InvalidFormat)
+        );
+    }
+}
@@ -1,21 +1,35 @@
 #![cfg_attr(not(test), no_std)]
 pub mod card;
 pub mod convert;
+pub mod deal_code;
 pub mod deck;
+pub mod dependencies;
+pub mod difficulty;
 pub mod engine;
+pub mod estimate;
 pub mod formatter;
+pub mod generator;
+pub mod goal;
 pub mod graph;
 pub mod hidden;
 pub mod hop_solver;
+pub mod incremental;
+pub mod max_progress;
 pub mod mcts_solver;
 pub mod moves;
+pub mod notation;
+pub mod portable;
 pub mod pruning;
+#[cfg(feature = "serde")]
+mod serde_impl;
 pub mod shuffler;
 pub mod solver;
+pub mod split;
 pub mod stack;
 pub mod standard;
 pub mod state;
+pub mod svg;
 pub mod tracking;
 pub mod traverse;
-pub mod dependencies;
 mod utils;
+pub mod verify;
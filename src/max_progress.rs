@@ -0,0 +1,172 @@
+use crate::{
+    moves::{Move, MoveMask},
+    pruning::FullPruner,
+    solver::{HistoryVec, SearchResult},
+    state::{Encode, Solitaire},
+    tracking::{DefaultTerminateSignal, EmptySearchStats, SearchStatistics, TerminateSignal},
+    traverse::{traverse, Callback, Control, TpTable},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressScore {
+    /// Number of cards in the foundation
+    pub stack_len: u8,
+    /// Number of face-down cards left in the tableau (fewer is better)
+    pub down_cards: u8,
+}
+
+impl ProgressScore {
+    #[must_use]
+    pub fn new(game: &Solitaire) -> Self {
+        Self {
+            stack_len: game.get_stack().len(),
+            down_cards: game.get_hidden().total_down_cards(),
+        }
+    }
+
+    #[must_use]
+    const fn key(self, use_hidden: bool) -> (u8, u8) {
+        (
+            self.stack_len,
+            if use_hidden {
+                u8::MAX - self.down_cards
+            } else {
+                0
+            },
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ProgressResult {
+    pub result: SearchResult,
+    pub best: ProgressScore,
+    /// The line reaching the best position
+    pub history: HistoryVec,
+}
+
+struct ProgressCallback<'a, S: SearchStatistics, T: TerminateSignal> {
+    history: HistoryVec,
+    stats: &'a S,
+    sign: &'a T,
+    use_hidden: bool,
+    best: ProgressScore,
+    best_history: HistoryVec,
+    result: SearchResult,
+}
+
+impl<S: SearchStatistics, T: TerminateSignal> ProgressCallback<'_, S, T> {
+    fn update(&mut self, game: &Solitaire) {
+        let score = ProgressScore::new(game);
+        if score.key(self.use_hidden) > self.best.key(self.use_hidden) {
+            self.best = score;
+            self.best_history.clone_from(&self.history);
+        }
+    }
+}
+
+impl<S: SearchStatistics, T: TerminateSignal> Callback for ProgressCallback<'_, S, T> {
+    type Pruner = FullPruner;
+    // dominance moves only keep the solvability, not necessarily the best reachable position
+    const DOMINANCE: bool = false;
+
+    fn on_win(&mut self, game: &Solitaire) -> Control {
+        self.update(game);
+        self.result = SearchResult::Solved;
+        Control::Halt
+    }
+
+    fn on_visit(&mut self, game: &Solitaire, _: Encode) -> Control {
+        if self.sign.is_terminated() {
+            self.result = SearchResult::Terminated;
+            return Control::Halt;
+        }
+
+        self.stats.hit_a_state(self.history.len());
+        self.update(game);
+        Control::Ok
+    }
+
+    fn on_move_gen(&mut self, m: &MoveMask, _: Encode) -> Control {
+        self.stats.hit_unique_state(self.history.len(), m.len());
+        Control::Ok
+    }
+
+    fn on_do_move(&mut self, _: &Solitaire, m: Move, _: Encode, _: &FullPruner) -> Control {
+        self.history.push(m);
+        Control::Ok
+    }
+
+    fn on_undo_move(&mut self, _: Move, _: Encode, res: &Control) {
+        if *res == Control::Ok {
+            self.history.pop();
+        }
+        self.stats.finish_move(self.history.len());
+    }
+}
+
+/// Search for the position with the most cards in the foundation
+///
+/// When `use_hidden` is set, ties are broken by the fewest face-down cards left in the tableau.
+/// If the game is solvable, it stops at the first solution (with the `SearchResult::Solved` result)
+pub fn max_progress_with_tracking<S: SearchStatistics, T: TerminateSignal>(
+    game: &mut Solitaire,
+    use_hidden: bool,
+    stats: &S,
+    sign: &T,
+) -> ProgressResult {
+    let mut tp = TpTable::default();
+
+    let mut callback = ProgressCallback {
+        history: HistoryVec::new(),
+        stats,
+        sign,
+        use_hidden,
+        best: ProgressScore::new(game),
+        best_history: HistoryVec::new(),
+        result: SearchResult::Unsolvable,
+    };
+
+    traverse(game, FullPruner::default(), &mut tp, &mut callback);
+
+    ProgressResult {
+        result: callback.result,
+        best: callback.best,
+        history: callback.best_history,
+    }
+}
+
+pub fn max_progress(game: &mut Solitaire, use_hidden: bool) -> ProgressResult {
+    max_progress_with_tracking(
+        game,
+        use_hidden,
+        &EmptySearchStats {},
+        &DefaultTerminateSignal {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::shuffler::default_shuffle;
+
+    use super::*;
+
+    #[test]
+    fn test_max_progress() {
+        // this game is unsolvable
+        let game = Solitaire::new(&default_shuffle(8), NonZeroU8::new(3).unwrap());
+
+        for use_hidden in [false, true] {
+            let res = max_progress(&mut game.clone(), use_hidden);
+            assert_eq!(res.result, SearchResult::Unsolvable);
+
+            let mut g = game.clone();
+            for m in res.history {
+                g.do_move(m);
+            }
+            assert_eq!(ProgressScore::new(&g), res.best);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use core::{fmt, ops::ControlFlow};
 
 use crate::{
-    card::{Card, N_SUITS},
+    card::{Card, N_CARDS, N_SUITS},
     deck::N_PILES,
 };
 use arrayvec::ArrayVec;
@@ -27,6 +27,51 @@ impl fmt::Display for Move {
     }
 }
 
+impl Move {
+    #[must_use]
+    pub const fn card(self) -> Card {
+        match self {
+            Self::DeckStack(c)
+            | Self::PileStack(c)
+            | Self::DeckPile(c)
+            | Self::StackPile(c)
+            | Self::Reveal(c) => c,
+        }
+    }
+
+    /// Compact code of the move: the type in the high bits and the card (`rank * 4 + suit`) in the low 6 bits
+    #[must_use]
+    pub const fn code(self) -> u16 {
+        let kind: u16 = match self {
+            Self::DeckStack(_) => 0,
+            Self::PileStack(_) => 1,
+            Self::DeckPile(_) => 2,
+            Self::StackPile(_) => 3,
+            Self::Reveal(_) => 4,
+        };
+        let (rank, suit) = self.card().split();
+        kind << 6 | (rank * N_SUITS + suit) as u16
+    }
+
+    /// Inverse of `code`, returns `None` for invalid codes
+    #[must_use]
+    pub const fn from_code(code: u16) -> Option<Self> {
+        let value = (code & 0x3F) as u8;
+        if value >= N_CARDS {
+            return None;
+        }
+        let c = Card::new(value / N_SUITS, value % N_SUITS);
+        Some(match code >> 6 {
+            0 => Self::DeckStack(c),
+            1 => Self::PileStack(c),
+            2 => Self::DeckPile(c),
+            3 => Self::StackPile(c),
+            4 => Self::Reveal(c),
+            _ => return None,
+        })
+    }
+}
+
 #[derive(Default, PartialEq, Eq)]
 pub struct MoveMask {
     pub(crate) pile_stack: u64,
@@ -0,0 +1,333 @@
+use core::fmt;
+
+use crate::{
+    card::N_SUITS,
+    deck::N_PILES,
+    standard::{Pos, StandardHistoryVec, StandardMove, StandardSolitaire},
+    state::Solitaire,
+};
+
+extern crate alloc;
+use alloc::{format, string::String, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotationError {
+    /// The token at this index can't be read
+    InvalidToken(usize),
+    /// The move at this index is not legal in the position reached by the moves before it
+    IllegalMove(usize),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidToken(index) => write!(f, "can't read move {index}"),
+            Self::IllegalMove(index) => write!(f, "move {index} is illegal"),
+        }
+    }
+}
+
+impl core::error::Error for NotationError {}
+
+// MinimalKlondike letters: A for the stock, B..E for the foundation and F..L for the tableau
+fn minimal_letter(pos: Pos) -> char {
+    let id = match pos {
+        Pos::Deck => 0,
+        Pos::Stack(suit) => 1 + suit,
+        Pos::Pile(pile) => 1 + N_SUITS + pile,
+    };
+    char::from(b'A' + id)
+}
+
+fn from_minimal_letter(c: char) -> Option<Pos> {
+    let id = u8::try_from(c).ok()?.checked_sub(b'A')?;
+    match id {
+        0 => Some(Pos::Deck),
+        _ if id <= N_SUITS => Some(Pos::Stack(id - 1)),
+        _ if id <= N_SUITS + N_PILES => Some(Pos::Pile(id - 1 - N_SUITS)),
+        _ => None,
+    }
+}
+
+/// Write the moves in the notation of `MinimalKlondike` (`@` for each draw and two letters for the other moves)
+#[must_use]
+pub fn to_minimal_klondike(moves: &[StandardMove]) -> String {
+    let mut res = String::new();
+    for m in moves {
+        match (m.from, m.to) {
+            (Pos::Deck, Pos::Deck) => res.push('@'),
+            (from, to) => {
+                res.push(minimal_letter(from));
+                res.push(minimal_letter(to));
+                res.push(' ');
+            }
+        }
+    }
+    res
+}
+
+/// Read the moves of `game` in the notation of `MinimalKlondike`, whitespaces and commas are ignored
+///
+/// The moved cards are not written in this notation, so they are inferred by replaying the moves
+/// # Errors
+///
+/// Return the index (counting `@` and each pair of letters) of the first move which can't be read or isn't legal
+pub fn parse_minimal_klondike(
+    game: &Solitaire,
+    s: &str,
+) -> Result<StandardHistoryVec, NotationError> {
+    let mut standard = StandardSolitaire::from(game);
+    let mut moves = StandardHistoryVec::new();
+    let mut chars = s.chars().filter(|c| !c.is_whitespace() && *c != ',');
+
+    let mut index = 0;
+    while let Some(c) = chars.next() {
+        let (from, to) = if c == '@' {
+            (Pos::Deck, Pos::Deck)
+        } else {
+            chars
+                .next()
+                .and_then(|to| Some((from_minimal_letter(c)?, from_minimal_letter(to)?)))
+                .ok_or(NotationError::InvalidToken(index))?
+        };
+        let m = standard
+            .find_move(from, to)
+            .ok_or(NotationError::IllegalMove(index))?;
+        standard
+            .do_move(&m)
+            .map_err(|_| NotationError::IllegalMove(index))?;
+        moves
+            .try_push(m)
+            .map_err(|_| NotationError::IllegalMove(index))?;
+        index += 1;
+    }
+    Ok(moves)
+}
+
+// the suits of the foundation as written by Klondike-Solver
+const KS_SUITS: [char; N_SUITS as usize] = ['H', 'D', 'C', 'S'];
+
+fn ks_char(pos: Pos) -> char {
+    match pos {
+        Pos::Deck => 'W',
+        Pos::Stack(suit) => KS_SUITS[usize::from(suit)],
+        Pos::Pile(pile) => char::from(b'1' + pile),
+    }
+}
+
+fn from_ks_char(c: char) -> Option<Pos> {
+    let c = c.to_ascii_uppercase();
+    match c {
+        'W' => Some(Pos::Deck),
+        '1'..='7' => Some(Pos::Pile(u8::try_from(c).ok()? - b'1')),
+        _ => KS_SUITS
+            .iter()
+            .position(|&x| x == c)
+            .and_then(|suit| Some(Pos::Stack(u8::try_from(suit).ok()?))),
+    }
+}
+
+const fn is_redeal(game: &StandardSolitaire) -> bool {
+    let deck = game.get_deck();
+    deck.get_offset() >= deck.len()
+}
+
+/// Write the moves in the notation of Klondike-Solver
+///
+/// - `DR#`: draw `#` times, `NEW`: turn the waste over to the stock
+/// - `XY`: move the top card from `X` to `Y`, `XY-#`: move `#` cards between the tableau piles
+/// - `F#`: flip the hidden card of the tableau pile `#`
+///
+/// where the positions are `W` (the waste), `1`..`7` (the tableau) or the suit of the foundation (`H`, `D`, `C`, `S`)
+/// # Errors
+///
+/// Return the index of the first illegal move
+pub fn to_klondike_solver(
+    game: &Solitaire,
+    moves: &[StandardMove],
+) -> Result<String, NotationError> {
+    let mut standard = StandardSolitaire::from(game);
+    let mut tokens = Vec::<String>::new();
+    let mut n_draws = 0;
+
+    for (index, m) in moves.iter().enumerate() {
+        if *m == StandardMove::DRAW_NEXT && !is_redeal(&standard) {
+            n_draws += 1;
+        } else {
+            if n_draws > 0 {
+                tokens.push(format!("DR{n_draws}"));
+                n_draws = 0;
+            }
+
+            if *m == StandardMove::DRAW_NEXT {
+                tokens.push("NEW".into());
+            } else if let (Pos::Pile(from), Pos::Pile(_)) = (m.from, m.to) {
+                let pile = &standard.get_piles()[usize::from(from)];
+                let n_cards = pile.len() - pile.iter().position(|&c| c == m.card).unwrap_or(0);
+                if n_cards > 1 {
+                    tokens.push(format!("{}{}-{n_cards}", ks_char(m.from), ks_char(m.to)));
+                } else {
+                    tokens.push(format!("{}{}", ks_char(m.from), ks_char(m.to)));
+                }
+            } else {
+                tokens.push(format!("{}{}", ks_char(m.from), ks_char(m.to)));
+            }
+        }
+
+        let n_hidden = |g: &StandardSolitaire, pile: u8| g.get_hidden()[usize::from(pile)].len();
+        let hidden_before = match m.from {
+            Pos::Pile(pile) => n_hidden(&standard, pile),
+            _ => 0,
+        };
+
+        standard
+            .do_move(m)
+            .map_err(|_| NotationError::IllegalMove(index))?;
+
+        if let Pos::Pile(pile) = m.from {
+            if n_hidden(&standard, pile) < hidden_before {
+                tokens.push(format!("F{}", pile + 1));
+            }
+        }
+    }
+    if n_draws > 0 {
+        tokens.push(format!("DR{n_draws}"));
+    }
+    Ok(tokens.join(" "))
+}
+
+fn push(
+    game: &mut StandardSolitaire,
+    moves: &mut StandardHistoryVec,
+    m: StandardMove,
+    err: NotationError,
+) -> Result<(), NotationError> {
+    game.do_move(&m).map_err(|_| err)?;
+    moves.try_push(m).map_err(|_| err)
+}
+
+/// Read the moves of `game` in the notation of Klondike-Solver (see `to_klondike_solver`),
+/// the tokens are separated by whitespaces or commas
+///
+/// # Errors
+///
+/// Return the index of the first token which can't be read or isn't a legal move
+pub fn parse_klondike_solver(
+    game: &Solitaire,
+    s: &str,
+) -> Result<StandardHistoryVec, NotationError> {
+    let mut standard = StandardSolitaire::from(game);
+    let mut moves = StandardHistoryVec::new();
+
+    let tokens = s
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|x| !x.is_empty());
+
+    for (index, token) in tokens.enumerate() {
+        let invalid = NotationError::InvalidToken(index);
+        let illegal = NotationError::IllegalMove(index);
+
+        let token = token.to_ascii_uppercase();
+        if let Some(n) = token.strip_prefix("DR") {
+            let n: usize = n.parse().map_err(|_| invalid)?;
+            for _ in 0..n {
+                push(&mut standard, &mut moves, StandardMove::DRAW_NEXT, illegal)?;
+            }
+        } else if token == "NEW" {
+            if !is_redeal(&standard) {
+                return Err(illegal);
+            }
+            push(&mut standard, &mut moves, StandardMove::DRAW_NEXT, illegal)?;
+        } else if let Some(pile) = token.strip_prefix('F') {
+            // the hidden cards are revealed automatically
+            let pile: u8 = pile.parse().map_err(|_| invalid)?;
+            if !(1..=N_PILES).contains(&pile) {
+                return Err(invalid);
+            }
+        } else {
+            let (pos, n_cards) = match token.split_once('-') {
+                Some((pos, n)) => (pos, Some(n.parse::<usize>().map_err(|_| invalid)?)),
+                None => (token.as_str(), None),
+            };
+            let mut chars = pos.chars();
+            let (Some(from), Some(to), None) = (chars.next(), chars.next(), chars.next()) else {
+                return Err(invalid);
+            };
+            let from = from_ks_char(from).ok_or(invalid)?;
+            let to = from_ks_char(to).ok_or(invalid)?;
+            if to == Pos::Deck {
+                return Err(invalid);
+            }
+
+            let m = match (from, to) {
+                (Pos::Pile(pile), Pos::Pile(_)) => {
+                    let pile = &standard.get_piles()[usize::from(pile)];
+                    let n_cards = n_cards.unwrap_or(1);
+                    let card = *pile
+                        .len()
+                        .checked_sub(n_cards)
+                        .and_then(|i| pile.get(i))
+                        .ok_or(illegal)?;
+                    StandardMove::new(from, to, card)
+                }
+                _ if n_cards.is_some() => return Err(invalid),
+                _ => standard.find_move(from, to).ok_or(illegal)?,
+            };
+            push(&mut standard, &mut moves, m, illegal)?;
+        }
+    }
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::{convert::convert_moves, shuffler::default_shuffle, solver::solve};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for seed in 12..20 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+            let Some(moves) = solve(&mut game.clone()).1 else {
+                continue;
+            };
+            let standard = convert_moves(&mut StandardSolitaire::from(&game), &moves).unwrap();
+
+            let minimal = parse_minimal_klondike(&game, &to_minimal_klondike(&standard)).unwrap();
+            assert!(minimal == standard);
+
+            let text = to_klondike_solver(&game, &standard).unwrap();
+            let ks = parse_klondike_solver(&game, &text).unwrap();
+            assert!(ks == standard);
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+
+        assert_eq!(
+            parse_minimal_klondike(&game, "@ @ XY").unwrap_err(),
+            NotationError::InvalidToken(2)
+        );
+        assert_eq!(
+            parse_minimal_klondike(&game, "@ IB").unwrap_err(),
+            NotationError::IllegalMove(1)
+        );
+        assert_eq!(
+            parse_klondike_solver(&game, "DR1 NEW").unwrap_err(),
+            NotationError::IllegalMove(1)
+        );
+        assert_eq!(
+            parse_klondike_solver(&game, "DR1 F9").unwrap_err(),
+            NotationError::InvalidToken(1)
+        );
+        assert_eq!(
+            parse_klondike_solver(&game, "DR8 NEW").map(|m| m.len()),
+            Ok(9)
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! A self-contained serialization of a complete `Solitaire`
+//!
+//! `Solitaire::encode` is only meaningful for the game it comes from,
+//! this format keeps every card so the position can be restored without the original deal.
+//!
+//! The binary format (all counts are single bytes, a card is `rank * 4 + suit` with ♥, ♦, ♣, ♠):
+//! - `L`, the version, the draw step and the number of cards of each suit in the foundation
+//! - for each tableau pile: the number of hidden cards, the number of visible cards then the cards from the bottom
+//! - the number of cards in the deck, the size of the waste then the cards (the waste first then the stock)
+//!
+//! The text format holds the same parts on one line, with the cards written like `10H`:
+//! `v1 draw:3 foundation:1,0,0,0 piles:|KS,QH;2C|9D;... waste:AS,5D stock:3C,JH`
+//! where each pile is its hidden cards and its visible cards separated by `|`
+use core::{fmt, num::NonZeroU8};
+
+use arrayvec::ArrayVec;
+
+use crate::{
+    card::{Card, N_CARDS, N_RANKS, N_SUITS},
+    deck::{Deck, N_DECK_CARDS, N_PILES},
+    formatter::NUMBERS,
+    stack::Stack,
+    standard::{HiddenVec, PileVec, StandardSolitaire},
+    state::Solitaire,
+    utils::full_mask,
+};
+
+extern crate alloc;
+use alloc::{string::String, vec::Vec};
+
+pub const VERSION: u8 = 1;
+
+const MAGIC: u8 = b'L';
+const SUIT_LETTERS: [char; N_SUITS as usize] = ['H', 'D', 'C', 'S'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input doesn't start with the header of the format
+    InvalidHeader,
+    /// The input was written by an unknown version of the format
+    UnsupportedVersion(u8),
+    /// The input ends early or has trailing data
+    InvalidLength,
+    /// A card, a count or a section can't be read
+    InvalidValue,
+    /// The parts don't form a valid game (missing or duplicated cards, broken piles, ...)
+    InvalidState,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => f.write_str("invalid header"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported version {v}"),
+            Self::InvalidLength => f.write_str("invalid length"),
+            Self::InvalidValue => f.write_str("invalid value"),
+            Self::InvalidState => f.write_str("invalid game state"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+type DeckVec = ArrayVec<Card, { N_DECK_CARDS as usize }>;
+
+#[derive(Default)]
+struct Parts {
+    draw_step: u8,
+    foundation: [u8; N_SUITS as usize],
+    hidden: [HiddenVec; N_PILES as usize],
+    piles: [PileVec; N_PILES as usize],
+    deck: DeckVec,
+    offset: u8,
+}
+
+impl Parts {
+    fn new(game: &Solitaire) -> Self {
+        let standard = StandardSolitaire::from(game);
+        let deck = game.get_deck();
+        Self {
+            draw_step: deck.draw_step().get(),
+            foundation: core::array::from_fn(|suit| {
+                #[allow(clippy::cast_possible_truncation)]
+                game.get_stack().get(suit as u8)
+            }),
+            hidden: standard.get_hidden().clone(),
+            piles: standard.get_piles().clone(),
+            deck: deck.iter().collect(),
+            offset: deck.get_offset(),
+        }
+    }
+
+    fn build(self) -> Result<Solitaire, DecodeError> {
+        let draw_step = NonZeroU8::new(self.draw_step).ok_or(DecodeError::InvalidValue)?;
+
+        let mut seen = 0u64;
+        let mut add = |c: Card| {
+            let dup = seen & c.mask() != 0;
+            seen |= c.mask();
+            if dup {
+                Err(DecodeError::InvalidState)
+            } else {
+                Ok(())
+            }
+        };
+
+        let mut stack_encode = 0u16;
+        for (suit, &cnt) in (0..N_SUITS).zip(&self.foundation) {
+            if cnt > N_RANKS {
+                return Err(DecodeError::InvalidState);
+            }
+            for rank in 0..cnt {
+                add(Card::new(rank, suit))?;
+            }
+            stack_encode |= u16::from(cnt) << (4 * suit);
+        }
+
+        for (i, (hidden, pile)) in self.hidden.iter().zip(&self.piles).enumerate() {
+            // a pile can't have more hidden cards than it was dealt, nor hidden cards without a visible one
+            if hidden.len() > i || (!hidden.is_empty() && pile.is_empty()) {
+                return Err(DecodeError::InvalidState);
+            }
+            if pile.windows(2).any(|w| !w[1].go_after(Some(w[0]))) {
+                return Err(DecodeError::InvalidState);
+            }
+            for &c in hidden.iter().chain(pile) {
+                add(c)?;
+            }
+        }
+
+        for &c in &self.deck {
+            add(c)?;
+        }
+        if seen != full_mask(N_CARDS) {
+            return Err(DecodeError::InvalidState);
+        }
+
+        let deck = Deck::from_parts(&self.deck, draw_step, self.offset)
+            .ok_or(DecodeError::InvalidState)?;
+        let standard = StandardSolitaire::from_parts(
+            self.hidden,
+            self.piles,
+            Stack::decode(stack_encode),
+            deck,
+        );
+        let game = Solitaire::from(&standard);
+        if game.is_valid() {
+            Ok(game)
+        } else {
+            Err(DecodeError::InvalidState)
+        }
+    }
+}
+
+const fn card_to_byte(c: Card) -> u8 {
+    c.rank() * N_SUITS + c.suit()
+}
+
+fn card_from_byte(b: u8) -> Result<Card, DecodeError> {
+    if b < N_CARDS {
+        Ok(Card::new(b / N_SUITS, b % N_SUITS))
+    } else {
+        Err(DecodeError::InvalidValue)
+    }
+}
+
+/// Write the complete state of `game` in the binary format
+#[must_use]
+pub fn to_bytes(game: &Solitaire) -> Vec<u8> {
+    let parts = Parts::new(game);
+    let mut res = Vec::with_capacity(usize::from(N_CARDS) + 32);
+    res.extend([MAGIC, VERSION, parts.draw_step]);
+    res.extend(parts.foundation);
+
+    for (hidden, pile) in parts.hidden.iter().zip(&parts.piles) {
+        #[allow(clippy::cast_possible_truncation)]
+        res.extend([hidden.len() as u8, pile.len() as u8]);
+        res.extend(hidden.iter().chain(pile).map(|&c| card_to_byte(c)));
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    res.extend([parts.deck.len() as u8, parts.offset]);
+    res.extend(parts.deck.iter().map(|&c| card_to_byte(c)));
+    res
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let (&first, rest) = self.0.split_first().ok_or(DecodeError::InvalidLength)?;
+        self.0 = rest;
+        Ok(first)
+    }
+
+    fn cards<const N: usize>(&mut self, len: u8) -> Result<ArrayVec<Card, N>, DecodeError> {
+        let mut res = ArrayVec::new();
+        for _ in 0..len {
+            res.try_push(card_from_byte(self.byte()?)?)
+                .map_err(|_| DecodeError::InvalidState)?;
+        }
+        Ok(res)
+    }
+}
+
+/// Read a game written by `to_bytes`
+///
+/// # Errors
+///
+/// Return the reason the input is not a valid game in a supported version
+pub fn from_bytes(bytes: &[u8]) -> Result<Solitaire, DecodeError> {
+    let mut reader = Reader(bytes);
+    if reader.byte()? != MAGIC {
+        return Err(DecodeError::InvalidHeader);
+    }
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let mut parts = Parts {
+        draw_step: reader.byte()?,
+        ..Default::default()
+    };
+    for cnt in &mut parts.foundation {
+        *cnt = reader.byte()?;
+    }
+    for (hidden, pile) in parts.hidden.iter_mut().zip(&mut parts.piles) {
+        let (n_hidden, n_visible) = (reader.byte()?, reader.byte()?);
+        *hidden = reader.cards(n_hidden)?;
+        *pile = reader.cards(n_visible)?;
+    }
+    let n_deck = reader.byte()?;
+    parts.offset = reader.byte()?;
+    parts.deck = reader.cards(n_deck)?;
+
+    if !reader.0.is_empty() {
+        return Err(DecodeError::InvalidLength);
+    }
+    parts.build()
+}
+
+fn push_cards(res: &mut String, cards: &[Card]) {
+    for (i, c) in cards.iter().enumerate() {
+        if i > 0 {
+            res.push(',');
+        }
+        res.push_str(NUMBERS[usize::from(c.rank())]);
+        res.push(SUIT_LETTERS[usize::from(c.suit())]);
+    }
+}
+
+/// Write the complete state of `game` in the text format
+#[must_use]
+pub fn to_text(game: &Solitaire) -> String {
+    use core::fmt::Write;
+
+    let parts = Parts::new(game);
+    let mut res = String::new();
+    let [a, b, c, d] = parts.foundation;
+    write!(
+        res,
+        "v{VERSION} draw:{} foundation:{a},{b},{c},{d} piles:",
+        parts.draw_step
+    )
+    .unwrap();
+
+    for (i, (hidden, pile)) in parts.hidden.iter().zip(&parts.piles).enumerate() {
+        if i > 0 {
+            res.push(';');
+        }
+        push_cards(&mut res, hidden);
+        res.push('|');
+        push_cards(&mut res, pile);
+    }
+
+    let (waste, stock) = parts.deck.split_at(usize::from(parts.offset));
+    res.push_str(" waste:");
+    push_cards(&mut res, waste);
+    res.push_str(" stock:");
+    push_cards(&mut res, stock);
+    res
+}
+
+fn parse_cards<const N: usize>(s: &str) -> Result<ArrayVec<Card, N>, DecodeError> {
+    let mut res = ArrayVec::new();
+    for c in s.split(',').filter(|x| !x.is_empty()) {
+        let card = c.parse().map_err(|_| DecodeError::InvalidValue)?;
+        res.try_push(card).map_err(|_| DecodeError::InvalidState)?;
+    }
+    Ok(res)
+}
+
+fn parse_count(s: &str) -> Result<u8, DecodeError> {
+    s.parse().map_err(|_| DecodeError::InvalidValue)
+}
+
+/// Read a game written by `to_text`
+///
+/// # Errors
+///
+/// Return the reason the input is not a valid game in a supported version
+pub fn from_text(s: &str) -> Result<Solitaire, DecodeError> {
+    let mut tokens = s.split_whitespace();
+    let version = tokens
+        .next()
+        .and_then(|x| x.strip_prefix('v'))
+        .ok_or(DecodeError::InvalidHeader)?;
+    let version = parse_count(version).map_err(|_| DecodeError::InvalidHeader)?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let mut field = |name: &str| {
+        tokens
+            .next()
+            .ok_or(DecodeError::InvalidLength)?
+            .strip_prefix(name)
+            .and_then(|x| x.strip_prefix(':'))
+            .ok_or(DecodeError::InvalidValue)
+    };
+
+    let mut parts = Parts {
+        draw_step: parse_count(field("draw")?)?,
+        ..Default::default()
+    };
+
+    let mut counts = field("foundation")?.split(',');
+    for cnt in &mut parts.foundation {
+        *cnt = parse_count(counts.next().ok_or(DecodeError::InvalidValue)?)?;
+    }
+    if counts.next().is_some() {
+        return Err(DecodeError::InvalidValue);
+    }
+
+    let mut piles = field("piles")?.split(';');
+    for (hidden, pile) in parts.hidden.iter_mut().zip(&mut parts.piles) {
+        let (h, p) = piles
+            .next()
+            .and_then(|x| x.split_once('|'))
+            .ok_or(DecodeError::InvalidValue)?;
+        *hidden = parse_cards(h)?;
+        *pile = parse_cards(p)?;
+    }
+    if piles.next().is_some() {
+        return Err(DecodeError::InvalidValue);
+    }
+
+    let waste: DeckVec = parse_cards(field("waste")?)?;
+    let stock: DeckVec = parse_cards(field("stock")?)?;
+    #[allow(clippy::cast_possible_truncation)]
+    let offset = waste.len() as u8;
+    parts.offset = offset;
+    parts.deck = waste;
+    for c in stock {
+        parts
+            .deck
+            .try_push(c)
+            .map_err(|_| DecodeError::InvalidState)?;
+    }
+
+    if tokens.next().is_some() {
+        return Err(DecodeError::InvalidLength);
+    }
+    parts.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use crate::{moves::N_MOVES_MAX, shuffler::default_shuffle};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = SmallRng::seed_from_u64(14);
+
+        for seed in 0..20 {
+            let mut game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+            for _ in 0..100 {
+                let bytes = to_bytes(&game);
+                let other = from_bytes(&bytes).unwrap();
+                assert!(other.equivalent_to(&game));
+                assert_eq!(to_bytes(&other), bytes);
+                assert!(other.gen_moves::<false>() == game.gen_moves::<false>());
+
+                let text = to_text(&game);
+                let other = from_text(&text).unwrap();
+                assert!(other.equivalent_to(&game));
+                assert_eq!(to_text(&other), text);
+
+                let moves = game.gen_moves::<false>().to_vec::<N_MOVES_MAX>();
+                let Some(&m) = moves.choose(&mut rng) else {
+                    break;
+                };
+                game.do_move(m);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(1).unwrap());
+        let bytes = to_bytes(&game);
+
+        assert_eq!(
+            from_bytes(&bytes[..10]).unwrap_err(),
+            DecodeError::InvalidLength
+        );
+        let mut bad = bytes.clone();
+        bad[1] = 9;
+        assert_eq!(
+            from_bytes(&bad).unwrap_err(),
+            DecodeError::UnsupportedVersion(9)
+        );
+        let mut bad = bytes.clone();
+        // the last card of the stock is the same as the one before
+        let n = bad.len();
+        bad[n - 1] = bad[n - 2];
+        assert_eq!(from_bytes(&bad).unwrap_err(), DecodeError::InvalidState);
+
+        let text = to_text(&game);
+        assert!(text.starts_with("v1 draw:1 foundation:0,0,0,0 piles:|"));
+        assert_eq!(
+            from_text(&text.replace("draw:1", "draw:0")).unwrap_err(),
+            DecodeError::InvalidValue
+        );
+        assert_eq!(
+            from_text(&text.replace("foundation:0,0,0,0", "foundation:1,0,0,0")).unwrap_err(),
+            DecodeError::InvalidState
+        );
+        assert_eq!(
+            from_text("v2").unwrap_err(),
+            DecodeError::UnsupportedVersion(2)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! `Serialize` and `Deserialize` for the core types (with the `serde` feature)
+//!
+//! The cards and the moves are written as strings in the same notation as their `Display`
+//! ("10♥", "DP 5♠", "5♠:3▸♠"), the foundation as the number of cards of each suit (♥, ♦, ♣, ♠)
+//! and the deck as its remaining cards with the draw step and the size of the waste
+use core::{fmt, marker::PhantomData, num::NonZeroU8, str::FromStr};
+
+use arrayvec::ArrayVec;
+use serde::{
+    de::{self, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    card::{Card, N_RANKS, N_SUITS},
+    deck::{Deck, N_DECK_CARDS},
+    moves::Move,
+    stack::Stack,
+    standard::{Pos, StandardMove},
+};
+
+struct FromStrVisitor<T>(PhantomData<T>);
+
+impl<T: FromStr> Visitor<'_> for FromStrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string in the notation of lonelybot")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+macro_rules! impl_string_serde {
+    ($($t:ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserializer.deserialize_str(FromStrVisitor(PhantomData))
+                }
+            }
+        )*
+    };
+}
+
+impl_string_serde!(Card, Pos, Move, StandardMove);
+
+impl Serialize for Stack {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let counts: [u8; N_SUITS as usize] = core::array::from_fn(|suit| {
+            #[allow(clippy::cast_possible_truncation)]
+            self.get(suit as u8)
+        });
+        counts.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Stack {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts = <[u8; N_SUITS as usize]>::deserialize(deserializer)?;
+        let mut encode = 0u16;
+        for (suit, &cnt) in counts.iter().enumerate() {
+            if cnt > N_RANKS {
+                return Err(de::Error::invalid_value(
+                    de::Unexpected::Unsigned(cnt.into()),
+                    &"at most 13 cards",
+                ));
+            }
+            encode |= u16::from(cnt) << (4 * suit);
+        }
+        Ok(Self::decode(encode))
+    }
+}
+
+impl Serialize for Deck {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let cards: ArrayVec<Card, { N_DECK_CARDS as usize }> = self.iter().collect();
+        let mut state = serializer.serialize_struct("Deck", 3)?;
+        state.serialize_field("draw_step", &self.draw_step().get())?;
+        state.serialize_field("cards", cards.as_slice())?;
+        state.serialize_field("offset", &self.get_offset())?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Deck")]
+struct DeckParts {
+    draw_step: NonZeroU8,
+    cards: ArrayVec<Card, { N_DECK_CARDS as usize }>,
+    offset: u8,
+}
+
+impl<'de> Deserialize<'de> for Deck {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parts = DeckParts::deserialize(deserializer)?;
+        Self::from_parts(&parts.cards, parts.draw_step, parts.offset)
+            .ok_or_else(|| de::Error::custom("invalid deck"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::{
+        hop_solver::HopResult, shuffler::default_shuffle, solver::SearchResult,
+        standard::StandardSolitaire,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_serde() {
+        let c = Card::new(9, 0);
+        assert_eq!(serde_json::to_string(&c).unwrap(), r#""10♥""#);
+        assert_eq!(serde_json::from_str::<Card>(r#""10h""#).unwrap(), c);
+        assert!(serde_json::from_str::<Card>(r#""11h""#).is_err());
+
+        let m = Move::DeckPile(Card::new(4, 3));
+        assert_eq!(serde_json::to_string(&m).unwrap(), r#""DP 5♠""#);
+        assert_eq!(serde_json::from_str::<Move>(r#""DP 5♠""#).unwrap(), m);
+
+        let m = StandardMove::new(Pos::Pile(2), Pos::Stack(3), Card::new(4, 3));
+        let text = serde_json::to_string(&m).unwrap();
+        assert_eq!(text, r#""5♠:3▸♠""#);
+        assert_eq!(serde_json::from_str::<StandardMove>(&text).unwrap(), m);
+
+        assert_eq!(
+            serde_json::to_string(&SearchResult::Solved).unwrap(),
+            r#""Solved""#
+        );
+        let res = HopResult {
+            wins: 1,
+            skips: 2,
+            played: 3,
+        };
+        assert_eq!(
+            serde_json::to_string(&res).unwrap(),
+            r#"{"wins":1,"skips":2,"played":3}"#
+        );
+    }
+
+    #[test]
+    fn test_serde_state() {
+        let mut game = StandardSolitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        game.do_move(&StandardMove::DRAW_NEXT).unwrap();
+
+        let stack = Stack::decode(0x1d03);
+        let text = serde_json::to_string(&stack).unwrap();
+        assert_eq!(text, "[3,0,13,1]");
+        assert_eq!(serde_json::from_str::<Stack>(&text).unwrap(), stack);
+        assert!(serde_json::from_str::<Stack>("[14,0,0,0]").is_err());
+
+        let deck = game.get_deck();
+        let text = serde_json::to_string(deck).unwrap();
+        let other: Deck = serde_json::from_str(&text).unwrap();
+        assert!(other.iter().eq(deck.iter()));
+        assert_eq!(other.get_offset(), deck.get_offset());
+        assert_eq!(other.draw_step(), deck.draw_step());
+        assert!(
+            serde_json::from_str::<Deck>(r#"{"draw_step":3,"cards":["AH","AH"],"offset":0}"#)
+                .is_err()
+        );
+    }
+}
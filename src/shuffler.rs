@@ -3,10 +3,15 @@ use crate::deck::{N_PILES, N_PILE_CARDS};
 use rand::prelude::*;
 
 use rand_mt::Mt;
-use uint::construct_uint;
-construct_uint! {
-    pub struct U256(4);
+
+#[allow(clippy::manual_div_ceil)]
+mod uint256 {
+    use uint::construct_uint;
+    construct_uint! {
+        pub struct U256(4);
+    }
 }
+pub use uint256::U256;
 
 pub type CardDeck = [Card; N_CARDS as usize];
 
@@ -261,6 +266,85 @@ pub fn microsoft_shuffle(mut seed: U256) -> Option<CardDeck> {
     Some(layer_to_pile(&cards))
 }
 
+struct PysolLcg {
+    seed: u32,
+}
+
+impl PysolLcg {
+    // the generator of Microsoft FreeCell
+    fn next_u32(&mut self) -> u32 {
+        self.seed = self.seed.wrapping_mul(214_013).wrapping_add(2_531_011) & 0x7fff_ffff;
+        self.seed >> 16
+    }
+}
+
+// `random.random()` of Python
+fn python_random(rng: &mut Mt) -> f64 {
+    let a = rng.next_u32() >> 5;
+    let b = rng.next_u32() >> 6;
+    (f64::from(a) * 67_108_864.0 + f64::from(b)) * (1.0 / 9_007_199_254_740_992.0)
+}
+
+/// Reproduce the numbered Klondike deals of `PySolFC`
+///
+/// The game numbers up to 32000 are shuffled by the generator of Microsoft `FreeCell`
+/// (so they match the `FreeCell` deals with the same number), the others by the Mersenne Twister of Python
+#[must_use]
+pub fn pysol_shuffle(seed: u128) -> CardDeck {
+    // clubs, spades, hearts then diamonds
+    const M: [u8; N_SUITS as usize] = [2, 3, 0, 1];
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut cards: CardDeck =
+        core::array::from_fn(|i| Card::new(i as u8 % N_RANKS, M[i / N_RANKS as usize]));
+
+    if seed <= 32000 {
+        // the order of Microsoft FreeCell: A♣ A♦ A♥ A♠ 2♣ ...
+        const ORDER: [usize; N_SUITS as usize] = [0, 39, 26, 13];
+        let base = cards;
+        for (i, c) in cards.iter_mut().enumerate() {
+            *c = base[i / 4 + ORDER[i % 4]];
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut rng = PysolLcg { seed: seed as u32 };
+        for i in (1..N_CARDS as usize).rev() {
+            let j = rng.next_u32() as usize % (i + 1);
+            cards.swap(i, j);
+        }
+    } else {
+        // Python seeds with the 32-bit words of the number
+        #[allow(clippy::cast_possible_truncation)]
+        let words: [u32; 4] = core::array::from_fn(|i| (seed >> (32 * i)) as u32);
+        let len = words.iter().rposition(|&x| x != 0).map_or(1, |x| x + 1);
+        let mut rng = Mt::new_with_key(words[..len].iter().copied());
+
+        for i in (1..N_CARDS as usize).rev() {
+            // `i` is below 52, so it is exact as `f64`
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                clippy::cast_precision_loss
+            )]
+            let j = (python_random(&mut rng) * (i + 1) as f64) as usize;
+            cards.swap(i, j);
+        }
+    }
+
+    // the cards are dealt from the end, the hidden layers from the last pile
+    // then the face up cards, the rest is the stock
+    cards.reverse();
+    let dealt = (0..N_PILES - 1)
+        .flat_map(|layer| (layer + 1..N_PILES).rev().map(move |pile| (pile, layer)))
+        .chain((0..N_PILES).rev().map(|pile| (pile, pile)));
+    let mut new_cards = cards;
+    for ((pile, layer), &c) in dealt.zip(&cards) {
+        new_cards[(pile * (pile + 1) / 2 + layer) as usize] = c;
+    }
+
+    new_cards
+}
+
 #[cfg(test)]
 mod tests {
     use rand::prelude::*;
@@ -278,6 +362,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pysol() {
+        fn parse(s: &str) -> Vec<Card> {
+            s.split_whitespace().map(|c| c.parse().unwrap()).collect()
+        }
+
+        fn layout(cards: &CardDeck) -> Vec<Vec<Card>> {
+            (0..N_PILES as usize)
+                .map(|i| cards[i * (i + 1) / 2..(i + 1) * (i + 2) / 2].to_vec())
+                .collect()
+        }
+
+        // the first row of FreeCell #1 is J♦ 2♦ 9♥ J♣ 5♦ 7♥ 7♣ 5♥,
+        // the same cards start the hidden layers of Klondike #1 from the last pile
+        let cards = pysol_shuffle(1);
+        let piles = layout(&cards);
+        assert_eq!(
+            (1..7).rev().map(|i| piles[i][0]).collect::<Vec<_>>(),
+            parse("JD 2D 9H JC 5D 7H")
+        );
+        assert_eq!([piles[6][1], piles[5][1]], [parse("7C")[0], parse("5H")[0]]);
+        assert_eq!(piles[0], parse("QH"));
+        assert_eq!(piles[6], parse("JD 7C 5S 3H 9D JS AS"));
+        assert_eq!(
+            cards[N_PILE_CARDS as usize..],
+            parse("4H AC 4D 7S 3S 10D 4S 10H 8H 2C JH 7D 6D 8S 8D QS 6C 3D 8C 10C 6S 9C 2H 6H")
+        );
+
+        // the deals above 32000, printed by script/pysol_deal.py which redoes the shuffle of PySolFC
+        // with `random.Random` of CPython 3.11.7
+        let cards = pysol_shuffle(123_456);
+        let expected = [
+            "3C",
+            "6H KD",
+            "AC 8S JD",
+            "9C 10C 2S 10S",
+            "2C 6C 10H 4H 9H",
+            "2D KC 9D AS QH 3S",
+            "3D 4C 8C JS AH 7H 8H",
+        ];
+        assert_eq!(
+            layout(&cards),
+            expected.iter().map(|x| parse(x)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            cards[N_PILE_CARDS as usize..],
+            parse("6D QS 6S 4S QC 10D KH 5D KS 7D 5H 7S 8D 2H 5S 9S JH JC 3H AD 4D 5C 7C QD")
+        );
+
+        let cards = pysol_shuffle(12_345_678_901_234_567_890);
+        assert_eq!(layout(&cards)[6], parse("AH 9S AC 10D AS QH KH"));
+    }
+
     #[test]
     fn test_encode2() {
         let mut rng = SmallRng::seed_from_u64(14);
@@ -285,10 +422,10 @@ mod tests {
         // for _ in 0..1000 {
         let seed: u64 = rng.random();
         let deck = default_shuffle(seed);
-        let encode = encode_shuffle(deck.clone()).unwrap();
+        let encode = encode_shuffle(deck).unwrap();
         let deck_2 = exact_shuffle(encode).unwrap();
 
-        let encode2 = encode_shuffle(deck_2.clone()).unwrap();
+        let encode2 = encode_shuffle(deck_2).unwrap();
         assert_eq!(encode, encode2);
         assert_eq!(deck, deck_2);
         // }
@@ -9,11 +9,12 @@ use arrayvec::ArrayVec;
 
 // before every progress you'd do at most 2*N_RANKS move
 // and there would only be N_FULL_DECK + N_HIDDEN progress step
-const N_PLY_MAX: usize = 1024;
+pub(crate) const N_PLY_MAX: usize = 1024;
 
 pub type HistoryVec = ArrayVec<Move, N_PLY_MAX>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SearchResult {
     Terminated,
     Solved,
@@ -63,21 +64,24 @@ impl<S: SearchStatistics, T: TerminateSignal> Callback for SolverCallback<'_, S,
     }
 }
 
-pub fn solve_with_tracking<S: SearchStatistics, T: TerminateSignal>(
+// search from the state reached by `history`, with the pruner state at that point
+pub(crate) fn solve_from<S: SearchStatistics, T: TerminateSignal>(
     game: &mut Solitaire,
+    pruner: FullPruner,
+    history: HistoryVec,
     stats: &S,
     sign: &T,
 ) -> (SearchResult, Option<HistoryVec>) {
     let mut tp = TpTable::default();
 
     let mut callback = SolverCallback {
-        history: HistoryVec::new(),
+        history,
         stats,
         sign,
         result: SearchResult::Unsolvable,
     };
 
-    traverse(game, FullPruner::default(), &mut tp, &mut callback);
+    traverse(game, pruner, &mut tp, &mut callback);
 
     let result = callback.result;
 
@@ -88,6 +92,14 @@ pub fn solve_with_tracking<S: SearchStatistics, T: TerminateSignal>(
     }
 }
 
+pub fn solve_with_tracking<S: SearchStatistics, T: TerminateSignal>(
+    game: &mut Solitaire,
+    stats: &S,
+    sign: &T,
+) -> (SearchResult, Option<HistoryVec>) {
+    solve_from(game, FullPruner::default(), HistoryVec::new(), stats, sign)
+}
+
 pub fn solve(game: &mut Solitaire) -> (SearchResult, Option<HistoryVec>) {
     solve_with_tracking(game, &EmptySearchStats {}, &DefaultTerminateSignal {})
 }
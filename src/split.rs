@@ -0,0 +1,238 @@
+use core::{fmt, str::FromStr};
+
+use hashbrown::HashSet;
+
+use crate::{
+    moves::{Move, MoveMask},
+    pruning::{FullPruner, Pruner},
+    solver::{solve_from, HistoryVec, SearchResult},
+    state::{Encode, Solitaire},
+    tracking::{DefaultTerminateSignal, EmptySearchStats, SearchStatistics, TerminateSignal},
+    traverse::{traverse, Callback, Control, TpTable},
+    utils::MixHasherBuilder,
+};
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+/// A subtree of the search, given by the moves leading to it from the starting state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subproblem {
+    /// Encoding of the state at the root of the subtree (used for checking)
+    pub encode: Encode,
+    pub moves: HistoryVec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidSubproblem;
+
+impl fmt::Display for Subproblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.encode)?;
+        for m in &self.moves {
+            write!(f, " {}", m.code())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Subproblem {
+    type Err = InvalidSubproblem;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let encode = parts
+            .next()
+            .and_then(|x| x.parse().ok())
+            .ok_or(InvalidSubproblem)?;
+
+        let mut moves = HistoryVec::new();
+        for x in parts {
+            let m = x
+                .parse()
+                .ok()
+                .and_then(Move::from_code)
+                .ok_or(InvalidSubproblem)?;
+            moves.try_push(m).map_err(|_| InvalidSubproblem)?;
+        }
+        Ok(Self { encode, moves })
+    }
+}
+
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum SplitResult {
+    /// A solution is found before reaching the split depth
+    Solved(HistoryVec),
+    Parts(Vec<Subproblem>),
+}
+
+struct SplitCallback {
+    depth: usize,
+    history: HistoryVec,
+    seen: HashSet<Encode, MixHasherBuilder>,
+    parts: Vec<Subproblem>,
+    solved: bool,
+}
+
+impl Callback for SplitCallback {
+    type Pruner = FullPruner;
+
+    fn on_win(&mut self, _: &Solitaire) -> Control {
+        self.solved = true;
+        Control::Halt
+    }
+
+    fn on_visit(&mut self, _: &Solitaire, encode: Encode) -> Control {
+        if self.history.len() < self.depth {
+            return Control::Ok;
+        }
+        // the same state is only searched once, like the transposition table does
+        if self.seen.insert(encode) {
+            self.parts.push(Subproblem {
+                encode,
+                moves: self.history.clone(),
+            });
+        }
+        Control::Skip
+    }
+
+    fn on_do_move(&mut self, _: &Solitaire, m: Move, _: Encode, _: &FullPruner) -> Control {
+        self.history.push(m);
+        Control::Ok
+    }
+
+    fn on_undo_move(&mut self, _: Move, _: Encode, res: &Control) {
+        if *res != Control::Halt {
+            self.history.pop();
+        }
+    }
+}
+
+/// Split the search into the subtrees rooted at `depth` moves from `game`
+///
+/// The game is solvable if and only if one of the subproblems is solvable
+pub fn split(game: &mut Solitaire, depth: usize) -> SplitResult {
+    let mut tp = TpTable::default();
+    let mut callback = SplitCallback {
+        depth,
+        history: HistoryVec::new(),
+        seen: HashSet::default(),
+        parts: Vec::new(),
+        solved: false,
+    };
+
+    traverse(game, FullPruner::default(), &mut tp, &mut callback);
+
+    if callback.solved {
+        SplitResult::Solved(callback.history)
+    } else {
+        SplitResult::Parts(callback.parts)
+    }
+}
+
+/// Solve the subtree of `part`, `game` must be the same starting state given to `split`
+///
+/// The returned solution starts from `game`, not from the root of the subtree
+/// # Errors
+///
+/// Return `InvalidSubproblem` if the moves of `part` don't lead to the expected state
+pub fn solve_subproblem_with_tracking<S: SearchStatistics, T: TerminateSignal>(
+    game: &Solitaire,
+    part: &Subproblem,
+    stats: &S,
+    sign: &T,
+) -> Result<(SearchResult, Option<HistoryVec>), InvalidSubproblem> {
+    let mut game = game.clone();
+    let mut pruner = FullPruner::default();
+
+    // replay the moves to restore the pruner state
+    for &m in &part.moves {
+        let moves = game.gen_moves::<true>().filter(&pruner.prune_moves(&game));
+        if !MoveMask::from(m).filter(&moves).is_empty() {
+            return Err(InvalidSubproblem);
+        }
+        let (rev_m, (_, extra)) = game.do_move(m);
+        pruner = pruner.update(m, rev_m, extra);
+    }
+
+    if game.encode() != part.encode {
+        return Err(InvalidSubproblem);
+    }
+
+    Ok(solve_from(
+        &mut game,
+        pruner,
+        part.moves.clone(),
+        stats,
+        sign,
+    ))
+}
+
+/// # Errors
+///
+/// Return `InvalidSubproblem` if the moves of `part` don't lead to the expected state
+pub fn solve_subproblem(
+    game: &Solitaire,
+    part: &Subproblem,
+) -> Result<(SearchResult, Option<HistoryVec>), InvalidSubproblem> {
+    solve_subproblem_with_tracking(game, part, &EmptySearchStats {}, &DefaultTerminateSignal {})
+}
+
+/// Combine the results of every subproblem of a split
+///
+/// It is solved if any of them is solved, and unsolvable only if all of them are proven unsolvable
+pub fn merge_results<I: IntoIterator<Item = (SearchResult, Option<HistoryVec>)>>(
+    results: I,
+) -> (SearchResult, Option<HistoryVec>) {
+    let mut merged = SearchResult::Unsolvable;
+    for (res, history) in results {
+        match res {
+            SearchResult::Solved => return (res, history),
+            SearchResult::Unsolvable => {}
+            SearchResult::Crashed => merged = SearchResult::Crashed,
+            SearchResult::Terminated => {
+                if merged == SearchResult::Unsolvable {
+                    merged = SearchResult::Terminated;
+                }
+            }
+        }
+    }
+    (merged, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use alloc::string::ToString;
+
+    use crate::{shuffler::default_shuffle, solver::solve};
+
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        for seed in 0..8 {
+            let game = Solitaire::new(&default_shuffle(seed), NonZeroU8::new(3).unwrap());
+            let (expected, _) = solve(&mut game.clone());
+
+            let (res, history) = match split(&mut game.clone(), 4) {
+                SplitResult::Solved(history) => (SearchResult::Solved, Some(history)),
+                SplitResult::Parts(parts) => merge_results(parts.iter().map(|part| {
+                    let part: Subproblem = part.to_string().parse().unwrap();
+                    solve_subproblem(&game, &part).unwrap()
+                })),
+            };
+            assert_eq!(res, expected);
+
+            if let Some(history) = history {
+                let mut g = game.clone();
+                for m in history {
+                    g.do_move(m);
+                }
+                assert!(g.is_win());
+            }
+        }
+    }
+}
@@ -17,7 +17,7 @@ pub enum Pos {
     Pile(u8),
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct StandardMove {
     pub from: Pos,
     pub to: Pos,
@@ -43,7 +43,7 @@ const N_PLY_MAX: usize = 1024;
 pub type HiddenVec = ArrayVec<Card, N_HIDDEN_MAX>;
 pub type StandardHistoryVec = ArrayVec<StandardMove, N_PLY_MAX>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StandardSolitaire {
     final_stack: Stack,
     deck: Deck,
@@ -95,6 +95,21 @@ impl StandardSolitaire {
         }
     }
 
+    #[must_use]
+    pub(crate) const fn from_parts(
+        hidden_piles: [HiddenVec; N_PILES as usize],
+        piles: [PileVec; N_PILES as usize],
+        final_stack: Stack,
+        deck: Deck,
+    ) -> Self {
+        Self {
+            final_stack,
+            deck,
+            hidden_piles,
+            piles,
+        }
+    }
+
     #[must_use]
     pub const fn is_win(&self) -> bool {
         self.final_stack.is_full()
@@ -159,6 +174,30 @@ impl StandardSolitaire {
         None
     }
 
+    /// Find the legal move from `from` to `to` in the current state (for the notations without the moved card)
+    #[must_use]
+    pub fn find_move(&self, from: Pos, to: Pos) -> Option<StandardMove> {
+        let card = match (from, to) {
+            (Pos::Deck, Pos::Deck) => return Some(StandardMove::DRAW_NEXT),
+            (Pos::Deck, _) => self.deck.peek_current()?,
+            (Pos::Stack(suit), _) if suit < N_SUITS => {
+                Card::new(self.final_stack.get(suit).checked_sub(1)?, suit)
+            }
+            (Pos::Stack(_), _) => return None,
+            (Pos::Pile(from), Pos::Pile(to)) => {
+                let dst = self.piles.get(usize::from(to))?.last().copied();
+                *self
+                    .piles
+                    .get(usize::from(from))?
+                    .iter()
+                    .find(|c| c.go_after(dst))?
+            }
+            (Pos::Pile(from), _) => *self.piles.get(usize::from(from))?.last()?,
+        };
+        let m = StandardMove::new(from, to, card);
+        self.validate_move(&m).then_some(m)
+    }
+
     #[must_use]
     pub fn validate_move(&self, m: &StandardMove) -> bool {
         match (m.from, m.to, m.card) {
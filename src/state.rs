@@ -96,12 +96,12 @@ impl Solitaire {
     }
 
     #[must_use]
-    const fn get_visible_mask(&self) -> u64 {
+    pub(crate) const fn get_visible_mask(&self) -> u64 {
         self.visible_mask
     }
 
     #[must_use]
-    const fn get_locked_mask(&self) -> u64 {
+    pub(crate) const fn get_locked_mask(&self) -> u64 {
         self.hidden.get_locked_mask()
     }
 
@@ -637,7 +637,7 @@ mod tests {
                     .collect::<ArrayVec<(u8, Card), { N_DECK_CARDS as usize }>>();
 
                 test.clear();
-                game.deck.iter_callback(false, |pos, x| {
+                let _ = game.deck.iter_callback(false, |pos, x| {
                     test.push((pos, x));
                     ControlFlow::<()>::Continue(())
                 });
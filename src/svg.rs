@@ -0,0 +1,290 @@
+//! Render a position as an SVG image
+//!
+//! The stock and the waste are on the top left with the foundation on the top right,
+//! the hidden cards are drawn as backs and the waste is fanned for the draw steps above 1.
+use core::fmt::{self, Write};
+
+use crate::{
+    card::{Card, N_SUITS},
+    deck::N_PILES,
+    formatter::{NUMBERS, SYMBOLS},
+    standard::{Pos, StandardMove, StandardSolitaire},
+    state::Solitaire,
+};
+
+extern crate alloc;
+use alloc::string::String;
+
+const CARD_W: u32 = 60;
+const CARD_H: u32 = 84;
+const GAP: u32 = 12;
+const MARGIN: u32 = 12;
+const ROW_GAP: u32 = 24;
+const HIDDEN_STEP: u32 = 10;
+const VISIBLE_STEP: u32 = 24;
+const FAN_STEP: u32 = 16;
+// the waste shows at most this many cards
+const MAX_FAN: usize = 3;
+
+const TABLE_COLOR: &str = "#0b6623";
+const BACK_COLOR: &str = "#1f4e9c";
+const ARROW_COLOR: &str = "#ff8c00";
+const SUIT_COLORS: [&str; N_SUITS as usize] = ["#c00000", "#c00000", "#111111", "#111111"];
+
+const WIDTH: u32 = 2 * MARGIN + N_PILES as u32 * CARD_W + (N_PILES as u32 - 1) * GAP;
+const TABLEAU_Y: u32 = MARGIN + CARD_H + ROW_GAP;
+
+const fn column_x(col: u32) -> u32 {
+    MARGIN + col * (CARD_W + GAP)
+}
+
+const fn foundation_x(suit: u8) -> u32 {
+    column_x(N_PILES as u32 - N_SUITS as u32 + suit as u32)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn pile_card_y(n_hidden: usize, index: usize) -> u32 {
+    let n_hidden_above = if index < n_hidden { index } else { n_hidden };
+    TABLEAU_Y + n_hidden_above as u32 * HIDDEN_STEP + (index - n_hidden_above) as u32 * VISIBLE_STEP
+}
+
+/// A position with an optional move drawn as an arrow, displayed as an SVG document
+pub struct Svg<'a> {
+    game: &'a StandardSolitaire,
+    highlight: Option<StandardMove>,
+}
+
+impl<'a> Svg<'a> {
+    #[must_use]
+    pub const fn new(game: &'a StandardSolitaire, highlight: Option<StandardMove>) -> Self {
+        Self { game, highlight }
+    }
+
+    // the top left corner of the shown waste cards, the last one is the top of the waste
+    fn waste_x(&self) -> impl Iterator<Item = (u32, Card)> + '_ {
+        let deck = self.game.get_deck();
+        let n_fan = if deck.draw_step().get() > 1 {
+            MAX_FAN
+        } else {
+            1
+        };
+        let n_skip = deck.waste_iter().len().saturating_sub(n_fan);
+        deck.waste_iter()
+            .skip(n_skip)
+            .zip(0..)
+            .map(|(c, i)| (column_x(1) + i * FAN_STEP, c))
+    }
+
+    fn height(&self) -> u32 {
+        let piles = self.game.get_piles();
+        let bottom = (0..N_PILES as usize)
+            .map(|i| {
+                let n_hidden = self.game.get_hidden()[i].len();
+                let n_cards = n_hidden + piles[i].len();
+                pile_card_y(n_hidden, n_cards.saturating_sub(1))
+            })
+            .max()
+            .unwrap_or(TABLEAU_Y);
+        bottom + CARD_H + MARGIN
+    }
+
+    // where the arrow starts or ends for this position
+    fn anchor(&self, pos: Pos, card: Option<Card>) -> (u32, u32) {
+        let center = |x: u32, y: u32| (x + CARD_W / 2, y + CARD_H / 2);
+        match pos {
+            Pos::Deck => match self.waste_x().last() {
+                Some((x, _)) => center(x, MARGIN),
+                None => center(column_x(1), MARGIN),
+            },
+            Pos::Stack(suit) => center(foundation_x(suit), MARGIN),
+            Pos::Pile(pile) => {
+                let x = column_x(u32::from(pile)) + CARD_W / 2;
+                let pile = usize::from(pile);
+                let n_hidden = self.game.get_hidden()[pile].len();
+                let cards = &self.game.get_piles()[pile];
+                // the moved card, or the place of the next card
+                let index = card
+                    .and_then(|c| cards.iter().position(|&x| x == c))
+                    .unwrap_or(cards.len());
+                let y = pile_card_y(n_hidden, n_hidden + index);
+                (x, y + VISIBLE_STEP / 2)
+            }
+        }
+    }
+}
+
+fn write_face(f: &mut fmt::Formatter, x: u32, y: u32, card: Card) -> fmt::Result {
+    let (rank, suit) = card.split();
+    let (rank, color, symbol) = (
+        NUMBERS[usize::from(rank)],
+        SUIT_COLORS[usize::from(suit)],
+        SYMBOLS[usize::from(suit)],
+    );
+    writeln!(
+        f,
+        r##"<g transform="translate({x},{y})"><rect width="{CARD_W}" height="{CARD_H}" rx="5" fill="#ffffff" stroke="#333333"/><text x="4" y="17" font-size="15" font-weight="bold" fill="{color}">{rank}{symbol}</text><text x="{}" y="{}" font-size="30" text-anchor="middle" fill="{color}">{symbol}</text></g>"##,
+        CARD_W / 2,
+        CARD_H * 2 / 3 + 4,
+    )
+}
+
+fn write_back(f: &mut fmt::Formatter, x: u32, y: u32) -> fmt::Result {
+    writeln!(
+        f,
+        r##"<g transform="translate({x},{y})"><rect width="{CARD_W}" height="{CARD_H}" rx="5" fill="{BACK_COLOR}" stroke="#ffffff"/><rect x="5" y="5" width="{}" height="{}" rx="3" fill="none" stroke="#ffffff" stroke-opacity="0.5"/></g>"##,
+        CARD_W - 10,
+        CARD_H - 10,
+    )
+}
+
+fn write_slot(f: &mut fmt::Formatter, x: u32, y: u32, label: &str) -> fmt::Result {
+    writeln!(
+        f,
+        r##"<g transform="translate({x},{y})"><rect width="{CARD_W}" height="{CARD_H}" rx="5" fill="none" stroke="#ffffff" stroke-opacity="0.5" stroke-dasharray="4 3"/><text x="{}" y="{}" font-size="26" text-anchor="middle" fill="#ffffff" fill-opacity="0.5">{label}</text></g>"##,
+        CARD_W / 2,
+        CARD_H / 2 + 9,
+    )
+}
+
+impl fmt::Display for Svg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let height = self.height();
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="sans-serif">"#
+        )?;
+        writeln!(
+            f,
+            r#"<rect width="{WIDTH}" height="{height}" fill="{TABLE_COLOR}"/>"#
+        )?;
+
+        // stock
+        let deck = self.game.get_deck();
+        let n_stock = deck.deck_iter().len();
+        if n_stock > 0 {
+            write_back(f, column_x(0), MARGIN)?;
+            writeln!(
+                f,
+                r##"<text x="{}" y="{}" font-size="14" text-anchor="middle" fill="#ffffff">{n_stock}</text>"##,
+                column_x(0) + CARD_W / 2,
+                MARGIN + CARD_H / 2 + 5,
+            )?;
+        } else {
+            write_slot(f, column_x(0), MARGIN, "↻")?;
+        }
+
+        // waste
+        let mut empty = true;
+        for (x, c) in self.waste_x() {
+            write_face(f, x, MARGIN, c)?;
+            empty = false;
+        }
+        if empty {
+            write_slot(f, column_x(1), MARGIN, "")?;
+        }
+
+        // foundation
+        let stack = self.game.get_stack();
+        for suit in 0..N_SUITS {
+            match stack.get(suit) {
+                0 => write_slot(f, foundation_x(suit), MARGIN, SYMBOLS[usize::from(suit)])?,
+                n => write_face(f, foundation_x(suit), MARGIN, Card::new(n - 1, suit))?,
+            }
+        }
+
+        // tableau
+        for (i, (hidden, pile)) in self
+            .game
+            .get_hidden()
+            .iter()
+            .zip(self.game.get_piles())
+            .enumerate()
+        {
+            #[allow(clippy::cast_possible_truncation)]
+            let x = column_x(i as u32);
+            if hidden.is_empty() && pile.is_empty() {
+                write_slot(f, x, TABLEAU_Y, "")?;
+            }
+            for j in 0..hidden.len() {
+                write_back(f, x, pile_card_y(hidden.len(), j))?;
+            }
+            for (j, &c) in pile.iter().enumerate() {
+                write_face(f, x, pile_card_y(hidden.len(), hidden.len() + j), c)?;
+            }
+        }
+
+        if let Some(m) = self.highlight {
+            let ((x1, y1), (x2, y2)) = if m == StandardMove::DRAW_NEXT {
+                (
+                    (column_x(0) + CARD_W / 2, MARGIN + CARD_H / 2),
+                    (column_x(1) + CARD_W / 2, MARGIN + CARD_H / 2),
+                )
+            } else {
+                (self.anchor(m.from, Some(m.card)), self.anchor(m.to, None))
+            };
+            writeln!(
+                f,
+                r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="8" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{ARROW_COLOR}"/></marker></defs>"#
+            )?;
+            writeln!(
+                f,
+                r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{ARROW_COLOR}" stroke-width="5" stroke-opacity="0.9" marker-end="url(#arrow)"/>"#
+            )?;
+        }
+
+        writeln!(f, "</svg>")
+    }
+}
+
+/// Render `game` as an SVG document, with `highlight` drawn as an arrow
+#[must_use]
+pub fn to_svg(game: &StandardSolitaire, highlight: Option<StandardMove>) -> String {
+    let mut res = String::new();
+    write!(res, "{}", Svg::new(game, highlight)).unwrap();
+    res
+}
+
+/// Same as `to_svg` for a `Solitaire`
+#[must_use]
+pub fn solitaire_to_svg(game: &Solitaire, highlight: Option<StandardMove>) -> String {
+    to_svg(&StandardSolitaire::from(game), highlight)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::shuffler::default_shuffle;
+
+    use super::*;
+
+    #[test]
+    fn test_svg() {
+        let mut game = StandardSolitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let svg = to_svg(&game, None);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        // 21 hidden cards and the stock
+        assert_eq!(svg.matches(BACK_COLOR).count(), 22);
+        assert!(!svg.contains("marker-end"));
+
+        game.do_move(&StandardMove::DRAW_NEXT).unwrap();
+        let svg = to_svg(&game, Some(StandardMove::DRAW_NEXT));
+        assert!(svg.contains("marker-end"));
+        // the waste is fanned
+        let waste: alloc::vec::Vec<_> = game.get_deck().waste_iter().collect();
+        assert_eq!(waste.len(), 3);
+        for c in waste {
+            assert!(svg.contains(&alloc::format!(">{c}<")));
+        }
+
+        // the arrow starts on the moved card below the 3 hidden ones
+        let card = game.get_piles()[3][0];
+        assert_eq!(
+            Svg::new(&game, None).anchor(Pos::Pile(3), Some(card)),
+            (
+                column_x(3) + CARD_W / 2,
+                TABLEAU_Y + 3 * HIDDEN_STEP + VISIBLE_STEP / 2
+            )
+        );
+    }
+}
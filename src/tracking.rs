@@ -1,3 +1,5 @@
+use core::cell::Cell;
+
 pub trait SearchStatistics {
     fn hit_a_state(&self, depth: usize);
     fn hit_unique_state(&self, depth: usize, n_moves: u32);
@@ -22,3 +24,46 @@ pub trait TerminateSignal {
 pub struct DefaultTerminateSignal;
 
 impl TerminateSignal for DefaultTerminateSignal {}
+
+/// Count the unique states and terminate the search after `limit` of them (or when `sign` is terminated)
+pub struct StateLimit<'a, T: TerminateSignal> {
+    states: Cell<usize>,
+    limit: usize,
+    sign: &'a T,
+}
+
+impl<'a, T: TerminateSignal> StateLimit<'a, T> {
+    #[must_use]
+    pub const fn new(limit: usize, sign: &'a T) -> Self {
+        Self {
+            states: Cell::new(0),
+            limit,
+            sign,
+        }
+    }
+
+    #[must_use]
+    pub fn states(&self) -> usize {
+        self.states.get()
+    }
+
+    /// Whether the search is stopped by the limit
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.states.get() > self.limit
+    }
+}
+
+impl<T: TerminateSignal> SearchStatistics for StateLimit<'_, T> {
+    fn hit_a_state(&self, _: usize) {}
+    fn hit_unique_state(&self, _: usize, _: u32) {
+        self.states.set(self.states.get() + 1);
+    }
+    fn finish_move(&self, _: usize) {}
+}
+
+impl<T: TerminateSignal> TerminateSignal for StateLimit<'_, T> {
+    fn is_terminated(&self) -> bool {
+        self.is_exhausted() || self.sign.is_terminated()
+    }
+}
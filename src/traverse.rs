@@ -22,6 +22,14 @@ pub enum Control {
 pub trait Callback {
     type Pruner: Pruner;
 
+    // dominance moves assume that the goal is to win the game
+    // turn this off when using another goal
+    const DOMINANCE: bool = true;
+
+    fn is_goal(&self, game: &Solitaire) -> bool {
+        game.is_win()
+    }
+
     fn on_win(&mut self, game: &Solitaire) -> Control;
 
     fn on_visit(&mut self, _game: &Solitaire, _encode: Encode) -> Control {
@@ -66,7 +74,7 @@ pub fn traverse<T: TranspositionTable, C: Callback>(
     tp: &mut T,
     callback: &mut C,
 ) -> Control {
-    if game.is_win() {
+    if callback.is_goal(game) {
         return callback.on_win(game);
     }
 
@@ -82,9 +90,12 @@ pub fn traverse<T: TranspositionTable, C: Callback>(
         return Control::Ok;
     }
 
-    let move_list = game
-        .gen_moves::<true>()
-        .filter(&prune_info.prune_moves(game));
+    let move_list = if C::DOMINANCE {
+        game.gen_moves::<true>()
+    } else {
+        game.gen_moves::<false>()
+    }
+    .filter(&prune_info.prune_moves(game));
 
     match callback.on_move_gen(&move_list, encode) {
         Control::Halt => return Control::Halt,
@@ -0,0 +1,124 @@
+use core::fmt;
+
+use crate::{
+    convert::convert_move,
+    moves::Move,
+    standard::{Pos, StandardHistoryVec, StandardMove, StandardSolitaire},
+    state::Solitaire,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The move at this index is not legal in the position reached by the moves before it
+    IllegalMove(usize),
+    /// All the moves are legal but the game is not won after them
+    NotWon,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IllegalMove(index) => write!(f, "move {index} is illegal"),
+            Self::NotWon => f.write_str("the game is not won after all the moves"),
+        }
+    }
+}
+
+impl core::error::Error for VerifyError {}
+
+const fn check_win(game: &StandardSolitaire) -> Result<(), VerifyError> {
+    if game.is_win() {
+        Ok(())
+    } else {
+        Err(VerifyError::NotWon)
+    }
+}
+
+/// Replay the standard moves from `game` and check that they win it
+///
+/// # Errors
+///
+/// Return the index of the first illegal move, or `NotWon` if the game isn't won at the end
+pub fn verify_standard(game: &Solitaire, moves: &[StandardMove]) -> Result<(), VerifyError> {
+    let mut standard = StandardSolitaire::from(game);
+    for (index, m) in moves.iter().enumerate() {
+        standard
+            .do_move(m)
+            .map_err(|_| VerifyError::IllegalMove(index))?;
+    }
+    check_win(&standard)
+}
+
+/// Same as `verify_standard` but for the moves of `Solitaire`
+///
+/// Each move is converted to the standard moves in the position reached so far
+/// # Errors
+///
+/// Return the index of the first illegal move, or `NotWon` if the game isn't won at the end
+pub fn verify_moves(game: &Solitaire, moves: &[Move]) -> Result<(), VerifyError> {
+    let mut standard = StandardSolitaire::from(game);
+    let mut move_seq = StandardHistoryVec::new();
+    for (index, &m) in moves.iter().enumerate() {
+        move_seq.clear();
+        convert_move(&standard, m, &mut move_seq).map_err(|_| VerifyError::IllegalMove(index))?;
+        for m in &move_seq {
+            standard
+                .do_move(m)
+                .map_err(|_| VerifyError::IllegalMove(index))?;
+        }
+    }
+    check_win(&standard)
+}
+
+/// Same as `verify_standard` but for the moves given only by their source and destination
+/// (like the `MinimalKlondike` notation), the moved cards are inferred from the position
+///
+/// # Errors
+///
+/// Return the index of the first illegal move, or `NotWon` if the game isn't won at the end
+pub fn verify_positions(game: &Solitaire, moves: &[(Pos, Pos)]) -> Result<(), VerifyError> {
+    let mut standard = StandardSolitaire::from(game);
+    for (index, &(from, to)) in moves.iter().enumerate() {
+        let m = standard
+            .find_move(from, to)
+            .ok_or(VerifyError::IllegalMove(index))?;
+        standard
+            .do_move(&m)
+            .map_err(|_| VerifyError::IllegalMove(index))?;
+    }
+    check_win(&standard)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU8;
+
+    use crate::{convert::convert_moves, shuffler::default_shuffle, solver::solve};
+
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let game = Solitaire::new(&default_shuffle(12), NonZeroU8::new(3).unwrap());
+        let moves = solve(&mut game.clone()).1.unwrap();
+
+        assert_eq!(verify_moves(&game, &moves), Ok(()));
+        assert_eq!(
+            verify_moves(&game, &moves[..moves.len() - 1]),
+            Err(VerifyError::NotWon)
+        );
+        let mut bad = moves.clone();
+        bad.swap(0, moves.len() - 1);
+        assert_eq!(verify_moves(&game, &bad), Err(VerifyError::IllegalMove(0)));
+
+        let standard = convert_moves(&mut StandardSolitaire::from(&game), &moves).unwrap();
+        assert_eq!(verify_standard(&game, &standard), Ok(()));
+        assert_eq!(
+            verify_standard(&game, &standard[..standard.len() - 1]),
+            Err(VerifyError::NotWon)
+        );
+
+        let positions: Vec<_> = standard.iter().map(|m| (m.from, m.to)).collect();
+        assert_eq!(verify_positions(&game, &positions), Ok(()));
+    }
+}